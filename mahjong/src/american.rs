@@ -0,0 +1,453 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Deserialize;
+
+use crate::{
+    error::MahjongError,
+    hand::Hand,
+    tile::{Dragon, Flower, FlowerValue, Suit, Tile, TileValue, Wind},
+};
+
+const NUMBER_SUITS: [Suit; 3] = [Suit::Wan, Suit::Suo, Suit::Tong];
+
+// Every flower is interchangeable on the card
+const FLOWER: Tile = Tile::Flower(Flower::Red(FlowerValue::One));
+
+// One tile of a card pattern, with suits given as the index of the pattern's suit letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Spec {
+    Number(u8, usize),
+    Dragon(usize),
+    WhiteDragon,
+    Wind(Wind),
+    Flower,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardLine {
+    section: String,
+    pattern: String,
+    value: u32,
+    concealed: bool,
+    shift: bool,
+    groups: Vec<Vec<Spec>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Card {
+    year: u16,
+    lines: Vec<CardLine>,
+}
+
+#[derive(Deserialize)]
+struct CardConfig {
+    year: u16,
+    lines: Vec<LineConfig>,
+}
+
+#[derive(Deserialize)]
+struct LineConfig {
+    section: String,
+    pattern: String,
+    value: u32,
+    #[serde(default)]
+    concealed: bool,
+    #[serde(default)]
+    shift: bool,
+}
+
+fn parse_group(token: &str) -> Result<Vec<Spec>, MahjongError> {
+    let error = || MahjongError::CardParseError(format!("invalid group {token:?}"));
+    let (body, suit) = match token.chars().last() {
+        Some(c @ 'a'..='c') => (&token[..token.len() - 1], Some(c as usize - 'a' as usize)),
+        _ => (token, None),
+    };
+    if body.is_empty() {
+        return Err(error());
+    }
+    body.chars()
+        .map(|c| match c {
+            '1'..='9' => Ok(Spec::Number(c as u8 - b'0', suit.ok_or_else(error)?)),
+            '0' => Ok(Spec::WhiteDragon),
+            'D' => Ok(Spec::Dragon(suit.ok_or_else(error)?)),
+            'N' => Ok(Spec::Wind(Wind::North)),
+            'E' => Ok(Spec::Wind(Wind::East)),
+            'W' => Ok(Spec::Wind(Wind::West)),
+            'S' => Ok(Spec::Wind(Wind::South)),
+            'F' => Ok(Spec::Flower),
+            _ => Err(error()),
+        })
+        .collect()
+}
+
+fn numbered(suit: Suit, number: i8) -> Option<Tile> {
+    let value = TileValue::from_number(u8::try_from(number).ok()?)?;
+    match suit {
+        Suit::Wan => Some(Tile::Wan(value)),
+        Suit::Suo => Some(Tile::Suo(value)),
+        Suit::Tong => Some(Tile::Tong(value)),
+        _ => None,
+    }
+}
+
+// The three suit letters as every ordering of the numbered suits
+fn suit_assignments() -> Vec<[Suit; 3]> {
+    let mut assignments = Vec::new();
+    for a in NUMBER_SUITS {
+        for b in NUMBER_SUITS.into_iter().filter(|s| *s != a) {
+            for c in NUMBER_SUITS.into_iter().filter(|s| *s != a && *s != b) {
+                assignments.push([a, b, c]);
+            }
+        }
+    }
+    assignments
+}
+
+// Natural tiles of the hand counted by card tile, and the number of jokers
+fn hand_tiles(hand: &Hand) -> Option<(HashMap<Tile, u8>, u8)> {
+    let mut tiles = HashMap::new();
+    let mut jokers = 0;
+    let mut all_tiles: Vec<Tile> = hand.melds().iter().flat_map(|m| m.all_tiles()).collect();
+    for (tile, count) in hand.concealed().iter() {
+        all_tiles.extend(vec![*tile; *count as usize]);
+    }
+    all_tiles.extend(hand.bonus().iter());
+    for tile in all_tiles {
+        match tile {
+            Tile::Joker => jokers += 1,
+            Tile::Flower(_) => *tiles.entry(FLOWER).or_insert(0) += 1,
+            Tile::Animal(_) => return None,
+            _ => *tiles.entry(tile.normalized()).or_insert(0) += 1,
+        }
+    }
+    Some((tiles, jokers))
+}
+
+impl CardLine {
+    pub fn section(&self) -> &str {
+        &self.section
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    pub fn concealed(&self) -> bool {
+        self.concealed
+    }
+
+    // The groups as tiles for one choice of suits and shift, with whether jokers may be used
+    fn resolve(&self, suits: &[Suit; 3], shift: i8) -> Option<Vec<(Vec<Tile>, bool)>> {
+        self.groups
+            .iter()
+            .map(|group| {
+                let tiles = group
+                    .iter()
+                    .map(|spec| match spec {
+                        Spec::Number(n, s) => numbered(suits[*s], *n as i8 + shift),
+                        Spec::Dragon(s) => match suits[*s] {
+                            Suit::Wan => Some(Tile::Dragon(Dragon::Zhong)),
+                            Suit::Suo => Some(Tile::Dragon(Dragon::Fa)),
+                            _ => Some(Tile::Dragon(Dragon::Baiban)),
+                        },
+                        Spec::WhiteDragon => Some(Tile::Dragon(Dragon::Baiban)),
+                        Spec::Wind(w) => Some(Tile::Wind(*w)),
+                        Spec::Flower => Some(FLOWER),
+                    })
+                    .collect::<Option<Vec<Tile>>>()?;
+                let jokers_allowed = tiles.len() >= 3 && tiles.iter().all(|t| *t == tiles[0]);
+                Some((tiles, jokers_allowed))
+            })
+            .collect()
+    }
+
+    fn fits(
+        &self,
+        groups: &[(Vec<Tile>, bool)],
+        hand: &Hand,
+        tiles: &HashMap<Tile, u8>,
+        jokers: u8,
+    ) -> bool {
+        // [natural only, natural or joker]
+        let mut needed: HashMap<Tile, [u8; 2]> = HashMap::new();
+        for (group, jokers_allowed) in groups {
+            for tile in group {
+                needed.entry(*tile).or_default()[*jokers_allowed as usize] += 1;
+            }
+        }
+        if tiles.keys().any(|t| !needed.contains_key(t)) {
+            return false;
+        }
+        let mut jokers_needed = 0;
+        for (tile, [natural, any]) in needed.iter() {
+            let held = *tiles.get(tile).unwrap_or(&0);
+            if held < *natural || held > natural + any {
+                return false;
+            }
+            jokers_needed += natural + any - held;
+        }
+        if jokers_needed != jokers {
+            return false;
+        }
+
+        // Every exposure has to be one of the groups
+        let mut unused: Vec<&(Vec<Tile>, bool)> = groups.iter().collect();
+        for meld in hand.melds() {
+            let meld_tiles = meld.all_tiles();
+            let Some(natural) = meld_tiles.iter().find(|t| **t != Tile::Joker) else {
+                return false;
+            };
+            let Some(i) = unused.iter().position(|(group, jokers_allowed)| {
+                *jokers_allowed
+                    && group[0] == natural.normalized()
+                    && group.len() == meld_tiles.len()
+            }) else {
+                return false;
+            };
+            unused.remove(i);
+        }
+        true
+    }
+
+    pub fn matches(&self, hand: &Hand) -> bool {
+        if self.concealed && hand.melds().iter().any(|m| !m.is_concealed_set()) {
+            return false;
+        }
+        let Some((tiles, jokers)) = hand_tiles(hand) else {
+            return false;
+        };
+        let shifts = match self.shift {
+            true => -8..=8,
+            false => 0..=0,
+        };
+        shifts.into_iter().any(|shift| {
+            suit_assignments().iter().any(|suits| {
+                self.resolve(suits, shift)
+                    .is_some_and(|groups| self.fits(&groups, hand, &tiles, jokers))
+            })
+        })
+    }
+}
+
+impl Card {
+    pub fn from_toml(s: &str) -> Result<Self, MahjongError> {
+        let config: CardConfig =
+            toml::from_str(s).map_err(|e| MahjongError::CardParseError(e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, MahjongError> {
+        let config: CardConfig =
+            serde_json::from_str(s).map_err(|e| MahjongError::CardParseError(e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MahjongError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            Some("json") => Self::from_json(&contents),
+            _ => Err(MahjongError::CardParseError(format!(
+                "unsupported file extension for {}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn from_config(config: CardConfig) -> Result<Self, MahjongError> {
+        let mut lines = Vec::new();
+        for line in config.lines {
+            let groups = line
+                .pattern
+                .split_whitespace()
+                .map(parse_group)
+                .collect::<Result<Vec<_>, _>>()?;
+            if groups.iter().map(|g| g.len()).sum::<usize>() != 14 {
+                return Err(MahjongError::CardParseError(format!(
+                    "pattern {:?} does not have 14 tiles",
+                    line.pattern
+                )));
+            }
+            lines.push(CardLine {
+                section: line.section,
+                pattern: line.pattern,
+                value: line.value,
+                concealed: line.concealed,
+                shift: line.shift,
+                groups,
+            });
+        }
+        Ok(Self {
+            year: config.year,
+            lines,
+        })
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn lines(&self) -> &Vec<CardLine> {
+        &self.lines
+    }
+
+    // Lines the hand fits, in card order
+    pub fn matching_lines(&self, hand: &Hand) -> Vec<&CardLine> {
+        self.lines.iter().filter(|l| l.matches(hand)).collect()
+    }
+
+    pub fn best_line(&self, hand: &Hand) -> Option<&CardLine> {
+        self.matching_lines(hand)
+            .into_iter()
+            .max_by_key(|l| l.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_matches, assert_ok};
+
+    use crate::{
+        error::MahjongError,
+        hand::Hand,
+        meld::{Meld, MeldType},
+        tile::{Flower, FlowerValue, Tile, TileValue},
+    };
+
+    use super::Card;
+
+    const SAMPLE: &str = include_str!("../tables/american_sample.toml");
+
+    fn hand_from(tiles: &[Tile]) -> Hand {
+        let mut hand = Hand::new();
+        for tile in tiles {
+            hand.draw(tile);
+        }
+        hand
+    }
+
+    fn sections(card: &Card, hand: &Hand) -> Vec<String> {
+        card.matching_lines(hand)
+            .iter()
+            .map(|l| l.section().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_card() {
+        let card = Card::from_toml(SAMPLE).unwrap();
+        assert_eq!(card.year(), 2025);
+        assert_eq!(card.lines().len(), 7);
+        assert_matches!(
+            Card::from_toml(
+                "year = 2025\n[[lines]]\nsection = \"x\"\npattern = \"111 2222a\"\nvalue = 25"
+            ),
+            Err(MahjongError::CardParseError(_))
+        );
+    }
+
+    #[test]
+    fn test_jokers_in_groups() {
+        let card = Card::from_toml(SAMPLE).unwrap();
+        let hand = hand_from(&[
+            Tile::Suo(TileValue::Two),
+            Tile::Suo(TileValue::Two),
+            Tile::Suo(TileValue::Two),
+            Tile::Suo(TileValue::Four),
+            Tile::Suo(TileValue::Four),
+            Tile::Suo(TileValue::Four),
+            Tile::Joker,
+            Tile::Suo(TileValue::Six),
+            Tile::Suo(TileValue::Six),
+            Tile::Joker,
+            Tile::Suo(TileValue::Eight),
+            Tile::Suo(TileValue::Eight),
+            Tile::Suo(TileValue::Eight),
+            Tile::Suo(TileValue::Eight),
+        ]);
+        assert_eq!(sections(&card, &hand), vec!["2468"]);
+        assert_eq!(card.best_line(&hand).unwrap().value(), 25);
+    }
+
+    #[test]
+    fn test_jokers_not_in_pairs() {
+        let card = Card::from_toml(SAMPLE).unwrap();
+        let mut tiles = vec![
+            Tile::Tong(TileValue::Four),
+            Tile::Tong(TileValue::Five),
+            Tile::Tong(TileValue::Five),
+            Tile::Tong(TileValue::Five),
+            Tile::Tong(TileValue::Six),
+            Tile::Tong(TileValue::Six),
+            Tile::Tong(TileValue::Six),
+            Tile::Tong(TileValue::Six),
+            Tile::Tong(TileValue::Seven),
+            Tile::Tong(TileValue::Seven),
+            Tile::Tong(TileValue::Seven),
+            Tile::Tong(TileValue::Eight),
+            Tile::Tong(TileValue::Eight),
+        ];
+        tiles.push(Tile::Tong(TileValue::Four));
+        assert_eq!(sections(&card, &hand_from(&tiles)), vec!["Consecutive Run"]);
+        tiles.pop();
+        tiles.push(Tile::Joker);
+        assert!(sections(&card, &hand_from(&tiles)).is_empty());
+    }
+
+    #[test]
+    fn test_exposed_joker_pong() {
+        let card = Card::from_toml(SAMPLE).unwrap();
+        assert_err!(Meld::new(
+            vec![Tile::Wan(TileValue::Two), Tile::Joker],
+            None,
+            MeldType::Eye
+        ));
+        assert_err!(Meld::new(
+            vec![Tile::Joker, Tile::Joker],
+            Some(Tile::Joker),
+            MeldType::Pong
+        ));
+        let kong = assert_ok!(Meld::new(
+            vec![Tile::Wan(TileValue::Seven), Tile::Joker, Tile::Joker],
+            Some(Tile::Wan(TileValue::Seven)),
+            MeldType::Gang
+        ));
+        let pong = assert_ok!(Meld::new(
+            vec![Tile::Wan(TileValue::Seven), Tile::Joker],
+            Some(Tile::Wan(TileValue::Seven)),
+            MeldType::Pong
+        ));
+
+        let concealed = [
+            Tile::Flower(Flower::Red(FlowerValue::One)),
+            Tile::Flower(Flower::Blue(FlowerValue::Three)),
+            Tile::Suo(TileValue::Seven),
+            Tile::Suo(TileValue::Seven),
+            Tile::Suo(TileValue::Seven),
+            Tile::Joker,
+            Tile::Tong(TileValue::Seven),
+            Tile::Tong(TileValue::Seven),
+            Tile::Tong(TileValue::Seven),
+            Tile::Tong(TileValue::Seven),
+        ];
+        let mut hand = hand_from(&concealed);
+        hand.draw(&Tile::Wan(TileValue::Seven));
+        hand.draw(&Tile::Joker);
+        hand.draw(&Tile::Joker);
+        hand.meld(kong).unwrap();
+        assert_eq!(sections(&card, &hand), vec!["Like Numbers"]);
+
+        // The same tiles with only a pong exposed do not make up the kong
+        let mut hand = hand_from(&concealed);
+        hand.draw(&Tile::Wan(TileValue::Seven));
+        hand.draw(&Tile::Joker);
+        hand.draw(&Tile::Joker);
+        hand.meld(pong).unwrap();
+        assert!(sections(&card, &hand).is_empty());
+    }
+}
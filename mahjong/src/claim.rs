@@ -0,0 +1,98 @@
+use crate::{
+    meld::{Meld, MeldType},
+    round::KongKind,
+    tile::{Tile, Wind},
+};
+
+// Where a discarder sits as seen by the player claiming the discard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RelativeSeat {
+    // Plays just before the claimer, the only seat chi can be claimed from
+    Left,
+    Across,
+    // Plays just after the claimer
+    Right,
+}
+
+impl RelativeSeat {
+    // The discarder relative to the claimer, for seats listed in turn order
+    pub fn of(discarder: Wind, claimer: Wind, seats: &[Wind]) -> Option<RelativeSeat> {
+        let n = seats.len();
+        let discarder = seats.iter().position(|s| *s == discarder)?;
+        let claimer = seats.iter().position(|s| *s == claimer)?;
+        match (claimer + n - discarder) % n {
+            0 => None,
+            1 => Some(RelativeSeat::Left),
+            d if d == n - 1 => Some(RelativeSeat::Right),
+            _ => Some(RelativeSeat::Across),
+        }
+    }
+}
+
+// A legal way to claim the last discard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimOption {
+    Hu,
+    Gang(Meld),
+    Pong(Meld),
+    Chi(Meld),
+}
+
+impl ClaimOption {
+    pub fn from_meld(meld: Meld) -> Option<ClaimOption> {
+        match meld.meld_type() {
+            MeldType::Gang => Some(ClaimOption::Gang(meld)),
+            MeldType::Pong => Some(ClaimOption::Pong(meld)),
+            MeldType::Chi => Some(ClaimOption::Chi(meld)),
+            _ => None,
+        }
+    }
+
+    pub fn meld(&self) -> Option<&Meld> {
+        match self {
+            ClaimOption::Hu => None,
+            ClaimOption::Gang(meld) | ClaimOption::Pong(meld) | ClaimOption::Chi(meld) => {
+                Some(meld)
+            }
+        }
+    }
+}
+
+// A kong the player can declare on their own turn, either concealed or added to an exposed pong
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KongOption {
+    pub kind: KongKind,
+    pub tile: Tile,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tile::Wind;
+
+    use super::RelativeSeat;
+
+    #[test]
+    fn test_relative_seat() {
+        let seats = Wind::ALL;
+        assert_eq!(
+            RelativeSeat::of(Wind::East, Wind::South, &seats),
+            Some(RelativeSeat::Left)
+        );
+        assert_eq!(
+            RelativeSeat::of(Wind::East, Wind::West, &seats),
+            Some(RelativeSeat::Across)
+        );
+        assert_eq!(
+            RelativeSeat::of(Wind::East, Wind::North, &seats),
+            Some(RelativeSeat::Right)
+        );
+        assert_eq!(RelativeSeat::of(Wind::East, Wind::East, &seats), None);
+
+        let seats = [Wind::East, Wind::South, Wind::West];
+        assert_eq!(
+            RelativeSeat::of(Wind::East, Wind::West, &seats),
+            Some(RelativeSeat::Right)
+        );
+        assert_eq!(RelativeSeat::of(Wind::East, Wind::North, &seats), None);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    hu::Score,
+    tile::{Tile, Wind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Replacement {
+    Kong,
+    Flower,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WinContext {
+    pub winning_tile: Tile,
+    pub self_drawn: bool,
+    pub discarder: Option<Wind>,
+    pub replacement: Option<Replacement>,
+    pub robbing_kong: bool,
+    pub last_tile: bool,
+    // The winning tile is the last copy of its kind still unseen
+    pub last_copy: bool,
+    // Seat of the dealer, when known
+    pub dealer: Option<Wind>,
+    // Hands in a row the dealer has won or kept the deal
+    pub dealer_streak: u8,
+    pub seat_wind: Wind,
+    pub prevailing_wind: Wind,
+    // Every seat at the table, which settles the win
+    pub seats: Vec<Wind>,
+}
+
+impl WinContext {
+    // The winning tile has already been drawn into the hand
+    pub fn self_drawn(winning_tile: Tile, seat_wind: Wind, prevailing_wind: Wind) -> Self {
+        Self {
+            winning_tile,
+            self_drawn: true,
+            discarder: None,
+            replacement: None,
+            robbing_kong: false,
+            last_tile: false,
+            last_copy: false,
+            dealer: None,
+            dealer_streak: 0,
+            seat_wind,
+            prevailing_wind,
+            seats: Wind::ALL.to_vec(),
+        }
+    }
+
+    pub fn discard(
+        winning_tile: Tile,
+        discarder: Wind,
+        seat_wind: Wind,
+        prevailing_wind: Wind,
+    ) -> Self {
+        Self {
+            self_drawn: false,
+            discarder: Some(discarder),
+            ..Self::self_drawn(winning_tile, seat_wind, prevailing_wind)
+        }
+    }
+
+    pub fn with_replacement(mut self, replacement: Replacement) -> Self {
+        self.replacement = Some(replacement);
+        self
+    }
+
+    pub fn with_robbing_kong(mut self) -> Self {
+        self.robbing_kong = true;
+        self
+    }
+
+    pub fn with_last_tile(mut self) -> Self {
+        self.last_tile = true;
+        self
+    }
+
+    pub fn with_last_copy(mut self) -> Self {
+        self.last_copy = true;
+        self
+    }
+
+    pub fn with_seats(mut self, seats: &[Wind]) -> Self {
+        self.seats = seats.to_vec();
+        self
+    }
+
+    // The winner is the dealer
+    pub fn with_dealer(mut self) -> Self {
+        self.dealer = Some(self.seat_wind);
+        self
+    }
+
+    pub fn with_dealer_seat(mut self, seat: Wind) -> Self {
+        self.dealer = Some(seat);
+        self
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.dealer == Some(self.seat_wind)
+    }
+
+    pub fn with_dealer_streak(mut self, streak: u8) -> Self {
+        self.dealer_streak = streak;
+        self
+    }
+
+    // The tile claimed from another player, which is not yet in the hand
+    pub fn claimed_tile(&self) -> Option<&Tile> {
+        match self.self_drawn {
+            true => None,
+            false => Some(&self.winning_tile),
+        }
+    }
+
+    pub fn situational_scores(&self) -> Vec<Score> {
+        let mut scores = Vec::new();
+        if self.self_drawn {
+            match self.replacement {
+                Some(Replacement::Kong) => scores.push(Score::GangShang),
+                Some(Replacement::Flower) => scores.push(Score::HuaShang),
+                None => (),
+            }
+        }
+        if self.robbing_kong {
+            scores.push(Score::QiangGang);
+        }
+        if self.last_tile {
+            scores.push(Score::HaiDiLao);
+        }
+        if self.is_dealer() {
+            scores.extend(vec![Score::DealerStreak; self.dealer_streak as usize]);
+        }
+        scores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hu::Score,
+        tile::{Tile, TileValue, Wind},
+    };
+
+    use super::{Replacement, WinContext};
+
+    #[test]
+    fn test_situational_scores_self_drawn() {
+        let ctx = WinContext::self_drawn(Tile::Wan(TileValue::One), Wind::East, Wind::East)
+            .with_replacement(Replacement::Kong)
+            .with_last_tile();
+        assert_eq!(ctx.claimed_tile(), None);
+        assert_eq!(
+            ctx.situational_scores(),
+            vec![Score::GangShang, Score::HaiDiLao]
+        );
+    }
+
+    #[test]
+    fn test_situational_scores_robbing_kong() {
+        let ctx = WinContext::discard(
+            Tile::Wan(TileValue::One),
+            Wind::North,
+            Wind::East,
+            Wind::East,
+        )
+        .with_robbing_kong();
+        assert_eq!(ctx.claimed_tile(), Some(&Tile::Wan(TileValue::One)));
+        assert_eq!(ctx.situational_scores(), vec![Score::QiangGang]);
+    }
+
+    #[test]
+    fn test_situational_scores_dealer_streak() {
+        let ctx = WinContext::self_drawn(Tile::Wan(TileValue::One), Wind::East, Wind::East)
+            .with_dealer_streak(2);
+        assert_eq!(ctx.situational_scores(), vec![]);
+        assert_eq!(
+            ctx.with_dealer().situational_scores(),
+            vec![Score::DealerStreak, Score::DealerStreak]
+        );
+    }
+}
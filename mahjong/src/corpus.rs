@@ -0,0 +1,409 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{
+    context::{Replacement, WinContext},
+    error::MahjongError,
+    hand::Hand,
+    meld::{Meld, MeldType},
+    rules::{RuleSet, Scoring},
+    tile::{Animal, Dragon, Flower, FlowerValue, Tile, TileValue, Wind},
+};
+
+const SINGAPORE: &str = include_str!("../tables/singapore_corpus.toml");
+
+// Tile notation: digits followed by their suit, e.g. "123w 55t 0s".
+//   w, s, t   wan, suo and tong, with 0 for a red five
+//   z         honours, 1-4 for the winds from East and 5-7 for Zhong, Fa and Baiban
+//   r, b      red and blue flowers 1-4
+//   a         animals, 1-4 for cat, rat, chicken and centipede
+pub fn parse_tiles(s: &str) -> Result<Vec<Tile>, MahjongError> {
+    let error = || MahjongError::NotationError(s.to_string());
+    let mut tiles = Vec::new();
+    let mut digits = Vec::new();
+    for c in s.chars().filter(|c| !c.is_whitespace()) {
+        if let Some(digit) = c.to_digit(10) {
+            digits.push(digit as u8);
+            continue;
+        }
+        if digits.is_empty() {
+            return Err(error());
+        }
+        for digit in digits.drain(..) {
+            tiles.push(parse_tile(digit, c).ok_or_else(error)?);
+        }
+    }
+    match digits.is_empty() {
+        true => Ok(tiles),
+        false => Err(error()),
+    }
+}
+
+fn parse_tile(digit: u8, suit: char) -> Option<Tile> {
+    let value = || match digit {
+        0 => Some(TileValue::RedFive),
+        _ => TileValue::from_number(digit),
+    };
+    let flower = || {
+        [
+            FlowerValue::One,
+            FlowerValue::Two,
+            FlowerValue::Three,
+            FlowerValue::Four,
+        ]
+        .get(digit.checked_sub(1)? as usize)
+        .copied()
+    };
+    match suit {
+        'w' => value().map(Tile::Wan),
+        's' => value().map(Tile::Suo),
+        't' => value().map(Tile::Tong),
+        'z' => match digit {
+            1..=4 => Some(Tile::Wind(Wind::ALL[digit as usize - 1])),
+            5 => Some(Tile::Dragon(Dragon::Zhong)),
+            6 => Some(Tile::Dragon(Dragon::Fa)),
+            7 => Some(Tile::Dragon(Dragon::Baiban)),
+            _ => None,
+        },
+        'r' => flower().map(|f| Tile::Flower(Flower::Red(f))),
+        'b' => flower().map(|f| Tile::Flower(Flower::Blue(f))),
+        'a' => [Animal::Cat, Animal::Rat, Animal::Chicken, Animal::Centipede]
+            .get(digit.checked_sub(1)? as usize)
+            .map(|a| Tile::Animal(*a)),
+        _ => None,
+    }
+}
+
+// A hand in tile notation, where "[...]" is an exposed meld claiming its last tile and
+// "(...)" a concealed kong, e.g. "[555t] (1111z) 23w 456s 88t 1r"
+pub fn parse_hand(s: &str) -> Result<Hand, MahjongError> {
+    let error = || MahjongError::NotationError(s.to_string());
+    let mut hand = Hand::new();
+    for token in s.split_whitespace() {
+        let exposed = token.strip_prefix('[').and_then(|t| t.strip_suffix(']'));
+        let concealed = token.strip_prefix('(').and_then(|t| t.strip_suffix(')'));
+        let meld = match (exposed, concealed) {
+            (Some(t), _) => {
+                let mut tiles = parse_tiles(t)?;
+                let discarded = tiles.pop().ok_or_else(error)?;
+                let meld_type = match tiles.len() {
+                    3 => MeldType::Gang,
+                    _ if tiles
+                        .iter()
+                        .all(|t| t.normalized() == discarded.normalized()) =>
+                    {
+                        MeldType::Pong
+                    }
+                    _ => MeldType::Chi,
+                };
+                Meld::new(tiles, Some(discarded), meld_type)?
+            }
+            (None, Some(t)) => Meld::new(parse_tiles(t)?, None, MeldType::AnGang)?,
+            (None, None) => {
+                for tile in parse_tiles(token)? {
+                    hand.draw(&tile);
+                }
+                continue;
+            }
+        };
+        for tile in meld.tiles() {
+            hand.draw(tile);
+        }
+        hand.meld(meld)?;
+    }
+    Ok(hand)
+}
+
+fn parse_wind(s: &str) -> Result<Wind, MahjongError> {
+    Wind::ALL
+        .into_iter()
+        .find(|w| format!("{w:?}") == s)
+        .ok_or_else(|| MahjongError::NotationError(s.to_string()))
+}
+
+// The patterns and value a hand is scored with, patterns named as in their rule set
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Outcome {
+    pub patterns: Vec<String>,
+    pub value: u32,
+}
+
+impl<P: std::fmt::Debug> From<Scoring<P>> for Outcome {
+    fn from(scoring: Scoring<P>) -> Self {
+        let mut patterns: Vec<String> = scoring.patterns.iter().map(|p| format!("{p:?}")).collect();
+        patterns.sort();
+        Self {
+            patterns,
+            value: scoring.value,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CorpusConfig {
+    #[serde(default)]
+    case: Vec<CaseConfig>,
+}
+
+#[derive(Deserialize)]
+struct CaseConfig {
+    name: String,
+    // Tiles held before the winning tile
+    hand: String,
+    winning_tile: String,
+    #[serde(default)]
+    self_drawn: bool,
+    discarder: Option<String>,
+    seat_wind: String,
+    prevailing_wind: String,
+    replacement: Option<String>,
+    #[serde(default)]
+    robbing_kong: bool,
+    #[serde(default)]
+    last_tile: bool,
+    #[serde(default)]
+    dealer: bool,
+    #[serde(default)]
+    dealer_streak: u8,
+    // No expected outcome means the hand must not win
+    expected: Option<Outcome>,
+}
+
+#[derive(Debug)]
+pub struct Case {
+    name: String,
+    hand: Hand,
+    ctx: WinContext,
+    expected: Option<Outcome>,
+}
+
+impl Case {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    pub fn ctx(&self) -> &WinContext {
+        &self.ctx
+    }
+
+    pub fn expected(&self) -> Option<&Outcome> {
+        self.expected.as_ref()
+    }
+
+    fn from_config(config: CaseConfig) -> Result<Self, MahjongError> {
+        let mut hand = parse_hand(&config.hand)?;
+        let winning_tile = match parse_tiles(&config.winning_tile)?[..] {
+            [tile] => tile,
+            _ => return Err(MahjongError::NotationError(config.winning_tile)),
+        };
+        let seat_wind = parse_wind(&config.seat_wind)?;
+        let prevailing_wind = parse_wind(&config.prevailing_wind)?;
+        let mut ctx = match (config.self_drawn, config.discarder) {
+            (true, _) => {
+                hand.draw(&winning_tile);
+                WinContext::self_drawn(winning_tile, seat_wind, prevailing_wind)
+            }
+            (false, Some(discarder)) => WinContext::discard(
+                winning_tile,
+                parse_wind(&discarder)?,
+                seat_wind,
+                prevailing_wind,
+            ),
+            (false, None) => {
+                return Err(MahjongError::CorpusParseError(format!(
+                    "case {:?} is neither self drawn nor has a discarder",
+                    config.name
+                )))
+            }
+        };
+        ctx = match config.replacement.as_deref() {
+            Some("Kong") => ctx.with_replacement(Replacement::Kong),
+            Some("Flower") => ctx.with_replacement(Replacement::Flower),
+            Some(other) => return Err(MahjongError::NotationError(other.to_string())),
+            None => ctx,
+        };
+        if config.robbing_kong {
+            ctx = ctx.with_robbing_kong();
+        }
+        if config.last_tile {
+            ctx = ctx.with_last_tile();
+        }
+        if config.dealer {
+            ctx = ctx.with_dealer();
+        }
+        let ctx = ctx.with_dealer_streak(config.dealer_streak);
+        let expected = config.expected.map(|mut outcome| {
+            outcome.patterns.sort();
+            outcome
+        });
+        Ok(Self {
+            name: config.name,
+            hand,
+            ctx,
+            expected,
+        })
+    }
+}
+
+// A case scored differently from what the corpus expects
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub case: String,
+    pub expected: Option<Outcome>,
+    pub actual: Option<Outcome>,
+}
+
+// Winning hands with the outcome each should score, for regression testing rule sets
+#[derive(Debug)]
+pub struct Corpus {
+    cases: Vec<Case>,
+}
+
+impl Corpus {
+    pub fn singapore() -> Self {
+        Self::from_toml(SINGAPORE).expect("bundled Singapore corpus is valid")
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, MahjongError> {
+        let config: CorpusConfig =
+            toml::from_str(s).map_err(|e| MahjongError::CorpusParseError(e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, MahjongError> {
+        let config: CorpusConfig =
+            serde_json::from_str(s).map_err(|e| MahjongError::CorpusParseError(e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MahjongError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            Some("json") => Self::from_json(&contents),
+            _ => Err(MahjongError::CorpusParseError(format!(
+                "unsupported file extension for {}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn from_config(config: CorpusConfig) -> Result<Self, MahjongError> {
+        Ok(Self {
+            cases: config
+                .case
+                .into_iter()
+                .map(Case::from_config)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn cases(&self) -> &Vec<Case> {
+        &self.cases
+    }
+
+    // Scores every case with the rule set, returning each one that differs from the corpus
+    pub fn check<R: RuleSet>(&self, rules: &R) -> Vec<Mismatch> {
+        self.check_with(|hand, ctx| rules.score(hand, ctx))
+    }
+
+    // As check, with any scoring function such as a search_hu wrapper
+    pub fn check_with<P, F>(&self, score: F) -> Vec<Mismatch>
+    where
+        P: std::fmt::Debug,
+        F: Fn(&Hand, &WinContext) -> Option<Scoring<P>>,
+    {
+        self.cases
+            .iter()
+            .filter_map(|case| {
+                let actual = score(&case.hand, &case.ctx).map(Outcome::from);
+                match actual == case.expected {
+                    true => None,
+                    false => Some(Mismatch {
+                        case: case.name.clone(),
+                        expected: case.expected.clone(),
+                        actual,
+                    }),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::assert_err;
+
+    use crate::{
+        hu::search_hu,
+        rules::Scoring,
+        tai::ScoreTai,
+        tile::{Dragon, Flower, FlowerValue, Tile, TileValue},
+    };
+
+    use super::{parse_hand, parse_tiles, Corpus, Outcome};
+
+    #[test]
+    fn test_parse_notation() {
+        assert_eq!(
+            parse_tiles("109w 7z 2r").unwrap(),
+            vec![
+                Tile::Wan(TileValue::One),
+                Tile::Wan(TileValue::RedFive),
+                Tile::Wan(TileValue::Nine),
+                Tile::Dragon(Dragon::Baiban),
+                Tile::Flower(Flower::Red(FlowerValue::Two)),
+            ]
+        );
+        assert_err!(parse_tiles("12"));
+        assert_err!(parse_tiles("8z"));
+
+        let hand = parse_hand("[123w] (1111z) 45s 1b").unwrap();
+        assert_eq!(hand.melds().len(), 2);
+        assert_eq!(hand.concealed().values().sum::<u8>(), 2);
+        assert_eq!(hand.bonus().len(), 1);
+    }
+
+    #[test]
+    fn test_singapore_corpus() {
+        let corpus = Corpus::singapore();
+        assert!(!corpus.cases().is_empty());
+        assert_eq!(corpus.check(&ScoreTai::singapore()), vec![]);
+
+        let score_tai = ScoreTai::singapore();
+        let mismatches = Corpus::from_toml(
+            r#"
+            [[case]]
+            name = "pong is not ping hu"
+            hand = "23w 456s 789s 222t 88t"
+            winning_tile = "1w"
+            discarder = "East"
+            seat_wind = "South"
+            prevailing_wind = "East"
+            expected = { patterns = ["PingHu"], value = 4 }
+            "#,
+        )
+        .unwrap()
+        .check_with(|hand, ctx| {
+            search_hu(hand, ctx, &score_tai).map(|hu| Scoring {
+                melds: hu.melds().clone(),
+                patterns: hu.scores().clone(),
+                value: hu.tai() as u32,
+            })
+        });
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].actual,
+            Some(Outcome {
+                patterns: vec!["AllConcealed".to_string()],
+                value: 1,
+            })
+        );
+    }
+}
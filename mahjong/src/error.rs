@@ -1,13 +1,88 @@
 use thiserror::Error;
 
-use crate::{tile::{Tile, Suit}, meld::MeldType};
+use crate::{
+    hu::Score,
+    meld::MeldType,
+    round::Phase,
+    tile::{Suit, Tile, Wind},
+};
 
 #[derive(Error, Debug)]
 pub enum MahjongError {
-    #[error("Tile {0:?} not in hand")]
-    TileNotInHandFoundError(Tile),
+    #[error("Needs {1} of tile {0:?} but the hand holds {2}")]
+    InsufficientCopiesError(Tile, u8, u8),
     #[error("Tile with suit {0:?} is not playable")]
     TileNotPlayableError(Suit),
     #[error("Cannot create meld type {0:?} from {1:?} and {2:?}")]
-    InvalidMeldError(MeldType, Vec<Tile>, Option<Tile>)
+    InvalidMeldError(MeldType, Vec<Tile>, Option<Tile>),
+    #[error("A {0:?} needs {1} tiles, got {2}")]
+    WrongTileCountError(MeldType, usize, usize),
+    #[error("Tiles of a {0:?} must share a suit, got {1:?}")]
+    MixedSuitsError(MeldType, Vec<Suit>),
+    #[error("Chi tiles {0:?} are not consecutive")]
+    NonConsecutiveChiError(Vec<Tile>),
+    #[error("Honour tile {0:?} cannot be part of a chi")]
+    HonourChiError(Tile),
+    #[error("Unknown score {0:?}")]
+    UnknownScoreError(String),
+    #[error("Score {0:?} is missing from the tai table")]
+    MissingScoreError(Score),
+    #[error("Score {0:?} is worth {1} tai but the limit is {2}")]
+    InvalidLimitError(Score, u8, u8),
+    #[error("{0} points doubled {1} times do not fit in a u32")]
+    PointsOverflowError(u32, u8),
+    #[error("Score {0:?} is worth {1} tai but the cap is {2}")]
+    ScoreAboveCapError(Score, u8, u8),
+    #[error("Tai cap {0} is below the limit of {1}")]
+    InvalidCapError(u8, u8),
+    #[error("Limit score {0:?} cannot be listed as stacking on a limit hand")]
+    InvalidStackError(Score),
+    #[error("Cannot parse tai table: {0}")]
+    TaiTableParseError(String),
+    #[error("Cannot declare riichi: {0}")]
+    RiichiNotAllowedError(String),
+    #[error("Dead wall must have 14 tiles, got {0}")]
+    InvalidDeadWallError(usize),
+    #[error("No exposed pong of {0:?} to add a kong to")]
+    NoPongToUpgradeError(Tile),
+    #[error("It is not {0:?}'s turn")]
+    NotPlayersTurnError(Wind),
+    #[error("No player sits at {0:?}")]
+    SeatNotAtTableError(Wind),
+    #[error("{0:?} cannot act while the round waits for a {1:?}")]
+    WrongPhaseError(Wind, Phase),
+    #[error("Player {0:?} has already left the hand")]
+    PlayerOutError(Wind),
+    #[error("Discard {0:?} cannot be claimed")]
+    InvalidClaimError(Option<Tile>),
+    #[error("Claiming a {0:?} is not allowed")]
+    ClaimNotAllowedError(MeldType),
+    #[error("Cannot seat {0} players")]
+    InvalidSeatsError(usize),
+    #[error("Tile {0:?} is not played in this variant")]
+    RemovedTileError(Tile),
+    #[error("The wall is empty")]
+    EmptyWallError,
+    #[error("Hand of {0:?} is not a winning hand")]
+    NoHuError(Wind),
+    #[error("Tiles of the missing suit {0:?} must be discarded first and cannot be melded")]
+    MissingSuitError(Suit),
+    #[error("Player {0:?} has not declared a missing suit")]
+    MissingSuitNotDeclaredError(Wind),
+    #[error("Player {0:?} has already declared {1:?} as their missing suit")]
+    MissingSuitDeclaredError(Wind, Suit),
+    #[error("Cannot parse card: {0}")]
+    CardParseError(String),
+    #[error("Hand holds {0} tiles, expected {1} or one more")]
+    InvalidHandSizeError(usize, usize),
+    #[error("Tile {0:?} appears {1} times, at most 4 copies exist")]
+    TooManyCopiesError(Tile, usize),
+    #[error("Bonus tile {0:?} appears more than once")]
+    DuplicateBonusTileError(Tile),
+    #[error("Cannot parse tile notation {0:?}")]
+    NotationError(String),
+    #[error("Cannot parse corpus: {0}")]
+    CorpusParseError(String),
+    #[error("Cannot read config: {0}")]
+    ConfigReadError(#[from] std::io::Error),
 }
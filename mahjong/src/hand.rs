@@ -1,9 +1,11 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
+    claim::{ClaimOption, KongOption, RelativeSeat},
     error::MahjongError,
     meld::{Meld, MeldType},
-    tile::Tile,
+    round::KongKind,
+    tile::{Animal, Flower, FlowerColour, Tile, Wind},
 };
 
 #[derive(Debug, Default, Clone)]
@@ -28,22 +30,130 @@ impl ConcealedTiles {
                 self.0.remove(tile);
                 Ok(())
             }
-            Some(_) => Err(MahjongError::TileNotInHandFoundError(*tile)),
-            None => Err(MahjongError::TileNotInHandFoundError(*tile)),
+            Some(c) => Err(MahjongError::InsufficientCopiesError(*tile, n, *c)),
+            None => Err(MahjongError::InsufficientCopiesError(*tile, n, 0)),
         }
     }
 
     pub fn add_n(&mut self, tile: &Tile, n: u8) {
         *self.0.entry(*tile).or_insert(0) += n;
     }
+
+    // Merges red fives into regular fives
+    pub fn normalized(&self) -> ConcealedTiles {
+        let mut normalized = ConcealedTiles::default();
+        for (tile, count) in self.0.iter() {
+            normalized.add_n(&tile.normalized(), *count);
+        }
+        normalized
+    }
+}
+
+// Animals and flowers set aside as they are drawn
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BonusTiles(HashSet<Tile>);
+
+impl std::ops::Deref for BonusTiles {
+    type Target = HashSet<Tile>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl BonusTiles {
+    // Each pair is paid out on its own when both animals are held
+    pub const ANIMAL_PAIRS: [[Animal; 2]; 2] = [
+        [Animal::Cat, Animal::Rat],
+        [Animal::Chicken, Animal::Centipede],
+    ];
+
+    pub fn animals(&self) -> impl Iterator<Item = Animal> + '_ {
+        self.0.iter().filter_map(|t| match t {
+            Tile::Animal(animal) => Some(*animal),
+            _ => None,
+        })
+    }
+
+    pub fn flowers(&self) -> impl Iterator<Item = Flower> + '_ {
+        self.0.iter().filter_map(|t| match t {
+            Tile::Flower(flower) => Some(*flower),
+            _ => None,
+        })
+    }
+
+    pub fn has_all_animals(&self) -> bool {
+        self.animals().count() == 4
+    }
+
+    pub fn animal_pairs(&self) -> Vec<[Animal; 2]> {
+        Self::ANIMAL_PAIRS
+            .into_iter()
+            .filter(|pair| pair.iter().all(|a| self.0.contains(&Tile::Animal(*a))))
+            .collect()
+    }
+
+    pub fn flower_count(&self, colour: FlowerColour) -> usize {
+        self.flowers().filter(|f| f.colour() == colour).count()
+    }
+
+    // Colours of which all four flowers are held
+    pub fn complete_colours(&self) -> Vec<FlowerColour> {
+        [FlowerColour::Red, FlowerColour::Blue]
+            .into_iter()
+            .filter(|c| self.flower_count(*c) == 4)
+            .collect()
+    }
+
+    // The flowers numbered for the seat, one of each colour at most
+    pub fn seat_flowers(&self, seat: Wind) -> Vec<Flower> {
+        let mut flowers: Vec<Flower> = self.flowers().filter(|f| seat == f.value()).collect();
+        flowers.sort();
+        flowers
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Discard {
+    pub tile: Tile,
+    // Tsumogiri, the tile drawn this turn discarded straight away
+    pub from_draw: bool,
+    pub claimed_by: Option<Wind>,
+}
+
+// A player's discards in the order they were made
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct River(Vec<Discard>);
+
+impl std::ops::Deref for River {
+    type Target = Vec<Discard>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
 }
 
-#[derive(Debug, Default)]
+impl River {
+    pub fn contains_tile(&self, tile: &Tile) -> bool {
+        self.0
+            .iter()
+            .any(|d| d.tile.normalized() == tile.normalized())
+    }
+
+    // Discards still lying in the river, not taken by a claim
+    pub fn unclaimed(&self) -> impl Iterator<Item = &Discard> {
+        self.0.iter().filter(|d| d.claimed_by.is_none())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct Hand {
     concealed: ConcealedTiles,
     melds: Vec<Meld>,
-    bonus: HashSet<Tile>,
-    seen: HashSet<Tile>,
+    bonus: BonusTiles,
+    river: River,
+    // The tile drawn this turn, if it has not been discarded or a claim made since
+    last_drawn: Option<Tile>,
 }
 
 impl Hand {
@@ -57,25 +167,49 @@ impl Hand {
         match tile.is_playable() {
             true => {
                 self.concealed.add_n(tile, 1);
+                self.last_drawn = Some(*tile);
             }
             false => {
-                self.bonus.insert(*tile);
+                self.bonus.0.insert(*tile);
             }
         };
-        self.seen.clear();
     }
 
     pub fn discard(&mut self, tile: &Tile) -> Result<(), MahjongError> {
         self.concealed.remove_n(tile, 1)?;
-        self.seen.insert(tile.to_owned());
+        self.river.0.push(Discard {
+            tile: *tile,
+            from_draw: self.last_drawn.take() == Some(*tile),
+            claimed_by: None,
+        });
         Ok(())
     }
 
+    // Marks the latest discard as taken by the seat. When several players win on one discard
+    // the first of them is kept.
+    pub fn mark_claimed(&mut self, seat: Wind) -> Result<(), MahjongError> {
+        let discard = self
+            .river
+            .0
+            .last_mut()
+            .ok_or(MahjongError::InvalidClaimError(None))?;
+        discard.claimed_by.get_or_insert(seat);
+        Ok(())
+    }
+
+    pub fn river(&self) -> &River {
+        &self.river
+    }
+
     pub fn get_melds(&self, tile: &Tile) -> Result<Vec<Meld>, MahjongError> {
         if !tile.is_playable() {
             return Err(MahjongError::TileNotPlayableError(tile.suit()));
         }
         let mut poss_melds = vec![];
+        // A discarded joker is dead
+        if tile == &Tile::Joker {
+            return Ok(poss_melds);
+        }
         if let Some(num) = self.concealed.get(tile) {
             if *num >= 2 {
                 poss_melds.push(Meld::new(
@@ -98,10 +232,10 @@ impl Hand {
         let next_next = next.and_then(|t| t.next());
 
         let tiles_to_check = [prev_prev, prev, next, next_next];
-        let mut it = tiles_to_check.windows(2);
-        while let Some([Some(t1), Some(t2)]) = it.next() {
-            let mut tiles = [t1.to_owned(), t2.to_owned(), tile.to_owned()];
-            tiles.sort();
+        for window in tiles_to_check.windows(2) {
+            let [Some(t1), Some(t2)] = window else {
+                continue;
+            };
             if self.concealed.contains_key(t1) && self.concealed.contains_key(t2) {
                 poss_melds.push(Meld::new(
                     vec![t1.to_owned(), t2.to_owned()],
@@ -113,6 +247,20 @@ impl Hand {
         Ok(poss_melds)
     }
 
+    // Pong, gang and chi claims on a discard, chi only from the player on the left
+    pub fn claim_options(
+        &self,
+        tile: &Tile,
+        from: RelativeSeat,
+    ) -> Result<Vec<ClaimOption>, MahjongError> {
+        Ok(self
+            .get_melds(tile)?
+            .into_iter()
+            .filter(|m| m.meld_type() != &MeldType::Chi || from == RelativeSeat::Left)
+            .filter_map(ClaimOption::from_meld)
+            .collect())
+    }
+
     pub fn meld(&mut self, meld: Meld) -> Result<(), MahjongError> {
         let mut map = ConcealedTiles {
             ..Default::default()
@@ -123,32 +271,96 @@ impl Hand {
         for (tile, count) in map.iter() {
             self.concealed.remove_n(tile, *count)?;
         }
+        if meld.discarded_tile().is_some() {
+            self.last_drawn = None;
+        }
         self.melds.push(meld);
         Ok(())
     }
 
+    // Upgrades an exposed pong to a kong with the fourth tile from the concealed tiles
+    pub fn add_kong(&mut self, tile: &Tile) -> Result<(), MahjongError> {
+        let index = self
+            .melds
+            .iter()
+            .position(|m| m.meld_type() == &MeldType::Pong && m.all_tiles().contains(tile))
+            .ok_or(MahjongError::NoPongToUpgradeError(*tile))?;
+        self.concealed.remove_n(tile, 1)?;
+        let pong = &self.melds[index];
+        let mut tiles = pong.tiles().clone();
+        tiles.push(*tile);
+        let gang = Meld::new(tiles, pong.discarded_tile().copied(), MeldType::Gang)?;
+        self.melds[index] = match pong.provenance() {
+            Some(provenance) => gang.with_provenance(*provenance),
+            None => gang,
+        };
+        Ok(())
+    }
+
     pub fn get_angangs(&self) -> Vec<Meld> {
         let mut melds = Vec::new();
         for (tile, count) in self.concealed.iter() {
             if *count == 4 {
-                melds.push(
-                    Meld::new(
-                        vec![
-                            tile.to_owned(),
-                            tile.to_owned(),
-                            tile.to_owned(),
-                            tile.to_owned(),
-                        ],
-                        None,
-                        MeldType::AnGang,
-                    )
-                    .unwrap(),
-                );
+                // Four jokers alone are not a kong
+                if let Ok(meld) = Meld::new(
+                    vec![
+                        tile.to_owned(),
+                        tile.to_owned(),
+                        tile.to_owned(),
+                        tile.to_owned(),
+                    ],
+                    None,
+                    MeldType::AnGang,
+                ) {
+                    melds.push(meld);
+                }
             }
         }
         melds
     }
 
+    // Every kong that can be declared on this turn: concealed quads and pongs the fourth tile
+    // can be added to. The drawn tile, if given, has not been added to the hand yet. Jokers
+    // never count, as American mahjong, the only variant with jokers, exposes sets only when
+    // calling a discard.
+    pub fn kong_options(&self, drawn: Option<&Tile>) -> Vec<KongOption> {
+        let mut concealed = self.concealed.clone();
+        if let Some(tile) = drawn.filter(|t| t.is_playable()) {
+            concealed.add_n(tile, 1);
+        }
+        let mut tiles: Vec<&Tile> = concealed.keys().collect();
+        tiles.sort();
+        tiles.retain(|t| **t != Tile::Joker);
+
+        let mut options = Vec::new();
+        for tile in tiles.iter() {
+            if concealed[*tile] == 4 {
+                options.push(KongOption {
+                    kind: KongKind::Concealed,
+                    tile: **tile,
+                });
+            }
+        }
+        for pong in self
+            .melds
+            .iter()
+            .filter(|m| m.meld_type() == &MeldType::Pong)
+        {
+            let all_tiles = pong.all_tiles();
+            // A red five adds to a pong of plain fives and the other way round
+            let fourth = tiles
+                .iter()
+                .find(|t| all_tiles.iter().any(|m| m.normalized() == t.normalized()));
+            if let Some(tile) = fourth {
+                options.push(KongOption {
+                    kind: KongKind::Added,
+                    tile: **tile,
+                });
+            }
+        }
+        options
+    }
+
     pub fn concealed(&self) -> &ConcealedTiles {
         &self.concealed
     }
@@ -157,23 +369,83 @@ impl Hand {
         &self.melds
     }
 
-    pub fn bonus(&self) -> &HashSet<Tile> {
+    pub fn bonus(&self) -> &BonusTiles {
         &self.bonus
     }
+
+    // Concealed tiles plus three for every meld, so a kong does not change the size
+    pub fn size(&self) -> usize {
+        self.concealed.values().map(|c| *c as usize).sum::<usize>() + self.melds.len() * 3
+    }
+
+    // Every playable tile in the hand, concealed or melded
+    pub fn tiles(&self) -> Vec<Tile> {
+        let mut tiles: Vec<Tile> = self
+            .concealed
+            .iter()
+            .flat_map(|(t, c)| vec![*t; *c as usize])
+            .collect();
+        tiles.extend(self.melds.iter().flat_map(|m| m.all_tiles()));
+        tiles.sort();
+        tiles
+    }
+
+    // Checks the hand holds hand_size tiles, or one more before discarding, and no more than
+    // four copies of any tile
+    pub fn validate(&self, hand_size: usize) -> Result<(), MahjongError> {
+        let size = self.size();
+        if size != hand_size && size != hand_size + 1 {
+            return Err(MahjongError::InvalidHandSizeError(size, hand_size));
+        }
+        validate_table(&[self], &[])
+    }
+}
+
+// Checks the tiles of a whole table, the hands together with any other tiles in sight such as
+// discards or the wall: at most four copies of each tile, jokers aside, and each bonus tile
+// held at most once
+pub fn validate_table(hands: &[&Hand], visible: &[Tile]) -> Result<(), MahjongError> {
+    let mut bonus = HashSet::new();
+    let bonus_tiles = hands
+        .iter()
+        .flat_map(|h| h.bonus.iter())
+        .chain(visible.iter().filter(|t| !t.is_playable()));
+    for tile in bonus_tiles {
+        if !bonus.insert(*tile) {
+            return Err(MahjongError::DuplicateBonusTileError(*tile));
+        }
+    }
+
+    let mut counts: HashMap<Tile, usize> = HashMap::new();
+    let tiles = hands
+        .iter()
+        .flat_map(|h| h.tiles())
+        .chain(visible.iter().copied())
+        .filter(|t| t.is_playable() && *t != Tile::Joker);
+    for tile in tiles {
+        *counts.entry(tile.normalized()).or_insert(0) += 1;
+    }
+    match counts.into_iter().filter(|(_, c)| *c > 4).min() {
+        Some((tile, count)) => Err(MahjongError::TooManyCopiesError(tile, count)),
+        None => Ok(()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
-    use claim::{assert_err, assert_ok_eq};
+    use claim::{assert_err, assert_matches, assert_ok, assert_ok_eq};
 
     use crate::{
+        claim::{ClaimOption, KongOption, RelativeSeat},
+        error::MahjongError,
         meld::{Meld, MeldType},
-        tile::{Tile, TileValue},
+        round::KongKind,
+        tile::{Animal, Flower, FlowerColour, FlowerValue, Tile, TileValue, Wind},
     };
 
-    use super::Hand;
+    use super::{validate_table, Hand};
 
     #[test]
     fn test_draw_tile_ok() {
@@ -188,14 +460,6 @@ mod tests {
             .eq(&HashMap::from([(Tile::Wan(TileValue::One), 2)])));
     }
 
-    #[test]
-    fn test_no_seen_tiles_after_draw() {
-        let mut hand = Hand::new();
-        hand.seen.insert(Tile::Wan(TileValue::One));
-        hand.draw(&Tile::Suo(TileValue::One));
-        assert_eq!(hand.seen.len(), 0);
-    }
-
     #[test]
     fn test_discard_tile_ok() {
         let mut hand = Hand::new();
@@ -212,15 +476,32 @@ mod tests {
     #[test]
     fn test_discard_tile_not_in_hand_throws_error() {
         let mut hand = Hand::new();
-        assert_err!(hand.discard(&Tile::Wan(TileValue::One)));
+        assert_matches!(
+            hand.discard(&Tile::Wan(TileValue::One)),
+            Err(MahjongError::InsufficientCopiesError(_, 1, 0))
+        );
     }
 
     #[test]
-    fn test_seen_tile_after_discard() {
+    fn test_river_after_discard() {
         let mut hand = Hand::new();
         hand.draw(&Tile::Wan(TileValue::One));
+        hand.draw(&Tile::Wan(TileValue::Two));
         hand.discard(&Tile::Wan(TileValue::One)).unwrap();
-        assert!(hand.seen.contains(&Tile::Wan(TileValue::One)));
+        hand.draw(&Tile::Wan(TileValue::Three));
+        hand.discard(&Tile::Wan(TileValue::Three)).unwrap();
+        hand.mark_claimed(Wind::North).unwrap();
+        hand.mark_claimed(Wind::East).unwrap();
+
+        assert_eq!(
+            hand.river().iter().map(|d| d.tile).collect::<Vec<_>>(),
+            vec![Tile::Wan(TileValue::One), Tile::Wan(TileValue::Three)]
+        );
+        assert!(!hand.river()[0].from_draw);
+        assert!(hand.river()[1].from_draw);
+        assert_eq!(hand.river()[1].claimed_by, Some(Wind::North));
+        assert_eq!(hand.river().unclaimed().count(), 1);
+        assert!(hand.river().contains_tile(&Tile::Wan(TileValue::One)));
     }
 
     #[test]
@@ -234,7 +515,7 @@ mod tests {
         hand.draw(&Tile::Wan(TileValue::Five));
         hand.draw(&Tile::Wan(TileValue::Six));
 
-        let correct_melds = vec![
+        let correct_melds = [
             Meld::new(
                 vec![Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
                 Some(Tile::Wan(TileValue::Four)),
@@ -270,11 +551,37 @@ mod tests {
             )
             .unwrap(),
         ];
-        assert!(hand
-            .get_melds(&Tile::Wan(TileValue::Four))
-            .unwrap()
-            .iter()
-            .all(|m| correct_melds.contains(m)));
+        let melds = hand.get_melds(&Tile::Wan(TileValue::Four)).unwrap();
+        assert_eq!(melds.len(), correct_melds.len());
+        assert!(melds.iter().all(|m| correct_melds.contains(m)));
+
+        // A chi at the edge of the suit is still offered
+        assert_eq!(hand.get_melds(&Tile::Wan(TileValue::One)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_claim_options() {
+        let mut hand = Hand::new();
+        for value in [TileValue::Two, TileValue::Three, TileValue::Three] {
+            hand.draw(&Tile::Wan(value));
+        }
+        let tile = Tile::Wan(TileValue::Three);
+        let options = hand.claim_options(&tile, RelativeSeat::Across).unwrap();
+        assert_eq!(
+            options,
+            vec![ClaimOption::Pong(
+                Meld::new(vec![tile; 2], Some(tile), MeldType::Pong).unwrap()
+            )]
+        );
+        let tile = Tile::Wan(TileValue::One);
+        assert_eq!(
+            hand.claim_options(&tile, RelativeSeat::Right).unwrap(),
+            vec![]
+        );
+        assert_eq!(
+            hand.claim_options(&tile, RelativeSeat::Left).unwrap().len(),
+            1
+        );
     }
 
     #[test]
@@ -321,10 +628,130 @@ mod tests {
         hand.draw(&Tile::Wan(TileValue::Four));
         hand.draw(&Tile::Wan(TileValue::Four));
         hand.draw(&Tile::Wan(TileValue::Four));
-        let correct_melds = vec![
+        let correct_melds = [
             Meld::new(vec![Tile::Wan(TileValue::Two); 4], None, MeldType::AnGang).unwrap(),
             Meld::new(vec![Tile::Wan(TileValue::Three); 4], None, MeldType::AnGang).unwrap(),
         ];
         assert!(hand.get_angangs().iter().all(|m| correct_melds.contains(m)));
     }
+
+    #[test]
+    fn test_kong_options() {
+        let (two, three, four) = (
+            Tile::Wan(TileValue::Two),
+            Tile::Wan(TileValue::Three),
+            Tile::Wan(TileValue::Four),
+        );
+        let mut hand = Hand::new();
+        for tile in [two, two, two, two, three, three, four, four, four] {
+            hand.draw(&tile);
+        }
+        hand.meld(Meld::new(vec![three; 2], Some(three), MeldType::Pong).unwrap())
+            .unwrap();
+        hand.draw(&three);
+
+        let kong = |kind, tile| KongOption { kind, tile };
+        assert_eq!(
+            hand.kong_options(None),
+            vec![kong(KongKind::Concealed, two), kong(KongKind::Added, three)]
+        );
+        assert_eq!(
+            hand.kong_options(Some(&four)),
+            vec![
+                kong(KongKind::Concealed, two),
+                kong(KongKind::Concealed, four),
+                kong(KongKind::Added, three)
+            ]
+        );
+
+        let mut hand = Hand::new();
+        for tile in [two, two, two, Tile::Joker, three, three, Tile::Joker] {
+            hand.draw(&tile);
+        }
+        hand.meld(Meld::new(vec![three; 2], Some(three), MeldType::Pong).unwrap())
+            .unwrap();
+        assert!(hand.kong_options(None).is_empty());
+        assert!(hand.kong_options(Some(&Tile::Joker)).is_empty());
+
+        let (five, red_five) = (Tile::Tong(TileValue::Five), Tile::Tong(TileValue::RedFive));
+        let mut hand = Hand::new();
+        for tile in [five, five, two] {
+            hand.draw(&tile);
+        }
+        hand.meld(Meld::new(vec![five; 2], Some(five), MeldType::Pong).unwrap())
+            .unwrap();
+        assert!(hand.kong_options(None).is_empty());
+        assert_eq!(
+            hand.kong_options(Some(&red_five)),
+            vec![kong(KongKind::Added, red_five)]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut hand = Hand::new();
+        for value in [TileValue::One, TileValue::Two, TileValue::Three] {
+            for _ in 0..4 {
+                hand.draw(&Tile::Wan(value));
+            }
+        }
+        hand.draw(&Tile::Flower(Flower::Red(FlowerValue::One)));
+        assert_matches!(
+            hand.validate(13),
+            Err(MahjongError::InvalidHandSizeError(12, 13))
+        );
+        hand.draw(&Tile::Wan(TileValue::Four));
+        assert_ok!(hand.validate(13));
+        hand.meld(Meld::new(vec![Tile::Wan(TileValue::One); 4], None, MeldType::AnGang).unwrap())
+            .unwrap();
+        // A kong is short until its replacement tile is drawn
+        assert_err!(hand.validate(13));
+        hand.draw(&Tile::Wan(TileValue::Five));
+        assert_ok!(hand.validate(13));
+
+        let mut other = Hand::new();
+        other.draw(&Tile::Flower(Flower::Red(FlowerValue::One)));
+        assert_matches!(
+            validate_table(&[&hand, &other], &[]),
+            Err(MahjongError::DuplicateBonusTileError(_))
+        );
+        assert_matches!(
+            validate_table(&[&hand], &[Tile::Wan(TileValue::Two)]),
+            Err(MahjongError::TooManyCopiesError(
+                Tile::Wan(TileValue::Two),
+                5
+            ))
+        );
+    }
+
+    #[test]
+    fn test_bonus_tiles() {
+        let mut hand = Hand::new();
+        for animal in [Animal::Cat, Animal::Rat, Animal::Chicken] {
+            hand.draw(&Tile::Animal(animal));
+        }
+        for value in [
+            FlowerValue::One,
+            FlowerValue::Two,
+            FlowerValue::Three,
+            FlowerValue::Four,
+        ] {
+            hand.draw(&Tile::Flower(Flower::Red(value)));
+        }
+        hand.draw(&Tile::Flower(Flower::Blue(FlowerValue::Two)));
+
+        let bonus = hand.bonus();
+        assert!(!bonus.has_all_animals());
+        assert_eq!(bonus.animal_pairs(), vec![[Animal::Cat, Animal::Rat]]);
+        assert_eq!(bonus.flower_count(FlowerColour::Blue), 1);
+        assert_eq!(bonus.complete_colours(), vec![FlowerColour::Red]);
+        assert_eq!(
+            bonus.seat_flowers(Wind::South),
+            vec![
+                Flower::Red(FlowerValue::Two),
+                Flower::Blue(FlowerValue::Two)
+            ]
+        );
+        assert!(hand.concealed().is_empty());
+    }
 }
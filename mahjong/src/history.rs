@@ -0,0 +1,197 @@
+use crate::{
+    error::MahjongError,
+    hand::Hand,
+    meld::Meld,
+    round::{KongKind, Round},
+    tile::{Tile, Wind},
+};
+
+// State changed only through actions, so that a session can be stepped back and forward
+pub trait Replay: Clone {
+    type Action: Clone + std::fmt::Debug;
+
+    fn apply(&mut self, action: &Self::Action) -> Result<(), MahjongError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandAction {
+    Draw(Tile),
+    Discard(Tile),
+    Meld(Meld),
+    AddKong(Tile),
+}
+
+impl Replay for Hand {
+    type Action = HandAction;
+
+    fn apply(&mut self, action: &HandAction) -> Result<(), MahjongError> {
+        match action {
+            HandAction::Draw(tile) => {
+                self.draw(tile);
+                Ok(())
+            }
+            HandAction::Discard(tile) => self.discard(tile),
+            HandAction::Meld(meld) => self.meld(meld.clone()),
+            HandAction::AddKong(tile) => self.add_kong(tile),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoundAction {
+    Draw,
+    DrawReplacement,
+    Discard(Wind, Tile),
+    Claim(Wind, Meld),
+    Kong(Wind, KongKind, Tile),
+    Win(Wind, bool),
+}
+
+impl Replay for Round {
+    type Action = RoundAction;
+
+    fn apply(&mut self, action: &RoundAction) -> Result<(), MahjongError> {
+        match action {
+            RoundAction::Draw => self.draw().map(|_| ()),
+            RoundAction::DrawReplacement => self.draw_replacement().map(|_| ()),
+            RoundAction::Discard(seat, tile) => self.discard(*seat, tile),
+            RoundAction::Claim(seat, meld) => self.claim(*seat, meld.clone()),
+            RoundAction::Kong(seat, kind, tile) => self.kong(*seat, *kind, tile).map(|_| ()),
+            RoundAction::Win(seat, self_drawn) => self.win(*seat, *self_drawn),
+        }
+    }
+}
+
+// A command log over a hand or round. Each action keeps the state from before it, so undoing
+// restores that state exactly and redoing applies the action again.
+#[derive(Debug, Clone)]
+pub struct History<T: Replay> {
+    current: T,
+    done: Vec<(T::Action, T)>,
+    undone: Vec<T::Action>,
+}
+
+impl<T: Replay> History<T> {
+    pub fn new(initial: T) -> Self {
+        Self {
+            current: initial,
+            done: Vec::new(),
+            undone: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> &T {
+        &self.current
+    }
+
+    // Actions applied so far, oldest first
+    pub fn actions(&self) -> Vec<&T::Action> {
+        self.done.iter().map(|(action, _)| action).collect()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    // Applies a new action, which discards anything that could have been redone. A failed
+    // action leaves the state as it was.
+    pub fn apply(&mut self, action: T::Action) -> Result<(), MahjongError> {
+        self.step(action)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    fn step(&mut self, action: T::Action) -> Result<(), MahjongError> {
+        let mut next = self.current.clone();
+        next.apply(&action)?;
+        let previous = std::mem::replace(&mut self.current, next);
+        self.done.push((action, previous));
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> Option<T::Action> {
+        let (action, previous) = self.done.pop()?;
+        self.current = previous;
+        self.undone.push(action.clone());
+        Some(action)
+    }
+
+    pub fn redo(&mut self) -> Result<Option<T::Action>, MahjongError> {
+        let Some(action) = self.undone.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = self.step(action.clone()) {
+            self.undone.push(action);
+            return Err(e);
+        }
+        Ok(Some(action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_none, assert_ok};
+
+    use crate::{
+        hand::Hand,
+        round::{Round, RoundRules},
+        tile::{Tile, TileValue, Wind},
+    };
+
+    use super::{HandAction, History, RoundAction};
+
+    #[test]
+    fn test_hand_undo_redo() {
+        let one = Tile::Wan(TileValue::One);
+        let two = Tile::Wan(TileValue::Two);
+        let mut history = History::new(Hand::new());
+        history.apply(HandAction::Draw(one)).unwrap();
+        history.apply(HandAction::Draw(two)).unwrap();
+        history.apply(HandAction::Discard(one)).unwrap();
+        assert_err!(history.apply(HandAction::Discard(one)));
+        assert_eq!(history.actions().len(), 3);
+
+        // Oops, wrong tile
+        assert_eq!(history.undo(), Some(HandAction::Discard(one)));
+        assert_eq!(history.current().concealed()[&one], 1);
+        assert!(history.current().river().is_empty());
+        assert_eq!(history.redo().unwrap(), Some(HandAction::Discard(one)));
+        assert_eq!(history.current().river().len(), 1);
+
+        history.undo();
+        history.apply(HandAction::Discard(two)).unwrap();
+        assert!(!history.can_redo());
+        assert_none!(history.redo().unwrap());
+    }
+
+    #[test]
+    fn test_round_undo() {
+        let mut wall = Vec::new();
+        for number in 1..=9 {
+            let value = TileValue::from_number(number).unwrap();
+            for _ in 0..4 {
+                wall.extend([Tile::Wan(value), Tile::Suo(value), Tile::Tong(value)]);
+            }
+        }
+        let round = Round::new(&Wind::ALL, wall, Wind::East, RoundRules::default());
+        let mut history = History::new(round);
+
+        history.apply(RoundAction::Draw).unwrap();
+        let tile = Tile::Suo(TileValue::Five);
+        history
+            .apply(RoundAction::Discard(Wind::East, tile))
+            .unwrap();
+        assert_eq!(history.current().current(), Wind::South);
+
+        history.undo();
+        history.undo();
+        assert_eq!(history.current().current(), Wind::East);
+        assert_eq!(history.current().wall().len(), 56);
+        assert_ok!(history.redo());
+        assert_eq!(history.current().wall().len(), 55);
+    }
+}
@@ -0,0 +1,642 @@
+use std::collections::HashMap;
+
+use crate::{
+    context::{Replacement, WinContext},
+    error::MahjongError,
+    hand::{ConcealedTiles, Hand},
+    hu::{is_nine_gates, is_thirteen_wonders, search_melds, HandShape},
+    meld::{Meld, MeldType},
+    rules::{RuleSet, Scoring},
+    tile::{Suit, Tile, Wind},
+};
+
+// Hong Kong Old Style fan patterns
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fan {
+    SelfDrawn,
+    Concealed,
+    NoFlowers,
+    SeatFlower,
+    FlowerSet,
+    AllChows,
+    DragonPong,
+    SeatWind,
+    PrevailingWind,
+    RobbingKong,
+    WinOnKong,
+    WinOnLastTile,
+    MixedTerminals,
+    MixedOneSuit,
+    AllPongs,
+    SmallThreeDragons,
+    PureOneSuit,
+    GreatThreeDragons,
+    SmallFourWinds,
+    GreatFourWinds,
+    AllHonours,
+    AllTerminals,
+    FourConcealedPongs,
+    NineGates,
+    ThirteenOrphans,
+    EightFlowers,
+}
+
+impl Fan {
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Fan::SmallFourWinds
+                | Fan::GreatFourWinds
+                | Fan::AllHonours
+                | Fan::AllTerminals
+                | Fan::FourConcealedPongs
+                | Fan::NineGates
+                | Fan::ThirteenOrphans
+                | Fan::EightFlowers
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanHu {
+    melds: Vec<Meld>,
+    fans: Vec<Fan>,
+    fan: u8,
+}
+
+impl FanHu {
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn fans(&self) -> &Vec<Fan> {
+        &self.fans
+    }
+
+    pub fn fan(&self) -> u8 {
+        self.fan
+    }
+}
+
+impl std::cmp::Ord for FanHu {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.fan
+            .cmp(&other.fan)
+            .then_with(|| other.melds.cmp(&self.melds))
+            .then_with(|| other.fans.cmp(&self.fans))
+    }
+}
+
+impl std::cmp::PartialOrd for FanHu {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HongKong {
+    pub fan: HashMap<Fan, u8>,
+    pub limit: u8,
+    pub minimum: u8,
+    // Points paid for a zero fan hand, doubled for every fan up to the limit
+    pub base: u32,
+}
+
+impl Default for HongKong {
+    fn default() -> Self {
+        let limit = 10;
+        let mut fan = HashMap::from([
+            (Fan::SelfDrawn, 1),
+            (Fan::Concealed, 1),
+            (Fan::NoFlowers, 1),
+            (Fan::SeatFlower, 1),
+            (Fan::FlowerSet, 1),
+            (Fan::AllChows, 1),
+            (Fan::DragonPong, 1),
+            (Fan::SeatWind, 1),
+            (Fan::PrevailingWind, 1),
+            (Fan::RobbingKong, 1),
+            (Fan::WinOnKong, 1),
+            (Fan::WinOnLastTile, 1),
+            (Fan::MixedTerminals, 1),
+            (Fan::MixedOneSuit, 3),
+            (Fan::AllPongs, 3),
+            (Fan::SmallThreeDragons, 5),
+            (Fan::PureOneSuit, 7),
+            (Fan::GreatThreeDragons, 8),
+        ]);
+        for limit_fan in [
+            Fan::SmallFourWinds,
+            Fan::GreatFourWinds,
+            Fan::AllHonours,
+            Fan::AllTerminals,
+            Fan::FourConcealedPongs,
+            Fan::NineGates,
+            Fan::ThirteenOrphans,
+            Fan::EightFlowers,
+        ] {
+            fan.insert(limit_fan, limit);
+        }
+        Self {
+            fan,
+            limit,
+            minimum: 3,
+            base: 1,
+        }
+    }
+}
+
+impl HongKong {
+    fn new_hu(&self, mut melds: Vec<Meld>, fans: Vec<Fan>) -> FanHu {
+        let mut limits: Vec<Fan> = fans.iter().filter(|f| f.is_limit()).copied().collect();
+        let (mut fans, fan) = match limits.is_empty() {
+            true => {
+                let fan = fans
+                    .iter()
+                    .map(|f| *self.fan.get(f).unwrap_or(&0) as u32)
+                    .sum::<u32>()
+                    .min(self.limit as u32) as u8;
+                (fans, fan)
+            }
+            false => {
+                limits.sort();
+                limits.dedup();
+                (limits, self.limit)
+            }
+        };
+        melds.sort();
+        fans.sort();
+        FanHu { melds, fans, fan }
+    }
+
+    // Every interpretation of the hand, including those below the minimum, best first
+    pub fn search_hus(&self, hand: &Hand, ctx: &WinContext) -> Vec<FanHu> {
+        let mut concealed = hand.concealed().clone();
+        if let Some(tile) = ctx.claimed_tile() {
+            concealed.add_n(tile, 1);
+        }
+
+        let mut hand_fans = Vec::new();
+        if ctx.self_drawn {
+            hand_fans.push(Fan::SelfDrawn);
+            if ctx.replacement == Some(Replacement::Kong) {
+                hand_fans.push(Fan::WinOnKong);
+            }
+        }
+        if ctx.robbing_kong {
+            hand_fans.push(Fan::RobbingKong);
+        }
+        if ctx.last_tile {
+            hand_fans.push(Fan::WinOnLastTile);
+        }
+        if hand.melds().iter().all(|m| m.is_concealed_set()) {
+            hand_fans.push(Fan::Concealed);
+        }
+
+        // Flowers and seasons, animals are not part of the 8-flower set
+        let bonus = hand.bonus();
+        for _ in bonus.seat_flowers(ctx.seat_wind) {
+            hand_fans.push(Fan::SeatFlower);
+        }
+        if bonus.flowers().next().is_none() {
+            hand_fans.push(Fan::NoFlowers);
+        }
+        match bonus.complete_colours().len() {
+            2 => hand_fans.push(Fan::EightFlowers),
+            sets => hand_fans.extend(vec![Fan::FlowerSet; sets]),
+        }
+
+        let mut hus = Vec::new();
+        if hand.melds().is_empty() && is_thirteen_wonders(&concealed) {
+            let mut fans = hand_fans.clone();
+            fans.push(Fan::ThirteenOrphans);
+            hus.push(self.new_hu(Vec::new(), fans));
+        }
+
+        for poss_meld in search_melds(&concealed, HandShape::STANDARD.concealed_sets(hand)) {
+            let concealed_eye = poss_meld
+                .iter()
+                .find(|m| m.meld_type().eq(&MeldType::Eye))
+                .cloned();
+            let mut all_melds = hand.melds().clone();
+            all_melds.extend(poss_meld);
+            let mut fans = hand_fans.clone();
+            fans.extend(self.meld_fans(hand, ctx, &concealed, &all_melds, concealed_eye));
+            hus.push(self.new_hu(all_melds, fans));
+        }
+
+        hus.sort_by(|a, b| b.cmp(a));
+        hus.dedup();
+        hus
+    }
+
+    fn meld_fans(
+        &self,
+        hand: &Hand,
+        ctx: &WinContext,
+        concealed: &ConcealedTiles,
+        all_melds: &[Meld],
+        eye: Option<Meld>,
+    ) -> Vec<Fan> {
+        let mut fans = Vec::new();
+        let sets: Vec<&Meld> = all_melds
+            .iter()
+            .filter(|m| !m.meld_type().eq(&MeldType::Eye))
+            .collect();
+        let pongs: Vec<&Meld> = sets
+            .iter()
+            .filter(|m| !m.meld_type().eq(&MeldType::Chi))
+            .copied()
+            .collect();
+        let eye_suit = eye.as_ref().map(|m| *m.suit());
+
+        if pongs.is_empty() {
+            fans.push(Fan::AllChows);
+        }
+        if pongs.len() == sets.len() {
+            fans.push(Fan::AllPongs);
+            // The claimed tile may only complete the eye
+            let claimed_eye = match (ctx.claimed_tile(), &eye) {
+                (None, _) => true,
+                (Some(t), Some(e)) => e.tiles().contains(t),
+                _ => false,
+            };
+            if claimed_eye && pongs.iter().all(|m| m.is_concealed_set()) {
+                fans.push(Fan::FourConcealedPongs);
+            }
+        }
+
+        let dragon_pongs = pongs.iter().filter(|m| m.suit().eq(&Suit::Dragon)).count();
+        match (dragon_pongs, eye_suit) {
+            (3, _) => fans.push(Fan::GreatThreeDragons),
+            (2, Some(Suit::Dragon)) => fans.push(Fan::SmallThreeDragons),
+            (n, _) => fans.extend(vec![Fan::DragonPong; n]),
+        }
+
+        let wind_pongs: Vec<&Tile> = pongs
+            .iter()
+            .filter_map(|m| m.tiles().first())
+            .filter(|t| matches!(t, Tile::Wind(_)))
+            .collect();
+        match (wind_pongs.len(), eye_suit) {
+            (4, _) => fans.push(Fan::GreatFourWinds),
+            (3, Some(Suit::Wind)) => fans.push(Fan::SmallFourWinds),
+            _ => {
+                for tile in wind_pongs {
+                    if tile.eq(&Tile::Wind(ctx.seat_wind)) {
+                        fans.push(Fan::SeatWind);
+                    }
+                    if tile.eq(&Tile::Wind(ctx.prevailing_wind)) {
+                        fans.push(Fan::PrevailingWind);
+                    }
+                }
+            }
+        }
+
+        let mut number_suits: Vec<Suit> = all_melds
+            .iter()
+            .map(|m| *m.suit())
+            .filter(|s| matches!(s, Suit::Wan | Suit::Suo | Suit::Tong))
+            .collect();
+        number_suits.sort();
+        number_suits.dedup();
+        let has_honours = all_melds.iter().any(|m| m.tiles()[0].is_honour());
+        match (number_suits.len(), has_honours) {
+            (0, _) => fans.push(Fan::AllHonours),
+            (1, true) => fans.push(Fan::MixedOneSuit),
+            (1, false) => fans.push(Fan::PureOneSuit),
+            _ => (),
+        }
+
+        let all_tiles = all_melds.iter().flat_map(|m| m.tiles());
+        if all_tiles.clone().all(|t| t.is_terminal()) {
+            fans.push(Fan::AllTerminals);
+        } else if !number_suits.is_empty()
+            && all_tiles.clone().all(|t| t.is_terminal() || t.is_honour())
+        {
+            fans.push(Fan::MixedTerminals);
+        }
+
+        if hand.melds().is_empty()
+            && number_suits.len() == 1
+            && !has_honours
+            && is_nine_gates(concealed)
+        {
+            fans.push(Fan::NineGates);
+        }
+        fans
+    }
+
+    // Best interpretation that reaches the fan minimum
+    pub fn search_hu(&self, hand: &Hand, ctx: &WinContext) -> Option<FanHu> {
+        self.search_hus(hand, ctx)
+            .into_iter()
+            .next()
+            .filter(|hu| hu.fan >= self.minimum)
+    }
+
+    // A house limit or base set high enough can double the points past what fits in a u32
+    pub fn points(&self, fan: u8) -> Result<u32, MahjongError> {
+        let doublings = fan.min(self.limit);
+        1_u32
+            .checked_shl(doublings as u32)
+            .and_then(|p| p.checked_mul(self.base))
+            .ok_or(MahjongError::PointsOverflowError(self.base, doublings))
+    }
+
+    // A discarder pays double and the other players single; on a self-drawn win everyone
+    // pays double
+    pub fn payments(&self, hu: &FanHu, ctx: &WinContext) -> Result<Vec<(Wind, u32)>, MahjongError> {
+        let points = self.points(hu.fan)?;
+        let double = points
+            .checked_mul(2)
+            .ok_or(MahjongError::PointsOverflowError(points, 1))?;
+        Ok(ctx
+            .seats
+            .iter()
+            .copied()
+            .filter(|w| w != &ctx.seat_wind)
+            .map(|w| match ctx.self_drawn || ctx.discarder == Some(w) {
+                true => (w, double),
+                false => (w, points),
+            })
+            .collect())
+    }
+}
+
+impl RuleSet for HongKong {
+    type Pattern = Fan;
+
+    fn score_all(&self, hand: &Hand, ctx: &WinContext) -> Vec<Scoring<Fan>> {
+        self.search_hus(hand, ctx)
+            .into_iter()
+            .filter(|hu| hu.fan >= self.minimum)
+            .map(|hu| Scoring {
+                melds: hu.melds,
+                patterns: hu.fans,
+                value: hu.fan as u32,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_matches};
+
+    use crate::{
+        context::WinContext,
+        error::MahjongError,
+        hand::Hand,
+        meld::{Meld, MeldType},
+        rules::RuleSet,
+        tile::{Dragon, Flower, FlowerValue, Tile, TileValue, Wind},
+    };
+
+    use super::{Fan, HongKong};
+
+    fn draw_all(hand: &mut Hand, tiles: &[Tile]) {
+        for tile in tiles {
+            hand.draw(tile);
+        }
+    }
+
+    #[test]
+    fn test_mixed_one_suit() {
+        let mut hand = Hand::new();
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Wan(TileValue::One),
+                Tile::Wan(TileValue::Two),
+                Tile::Wan(TileValue::Three),
+                Tile::Wan(TileValue::Four),
+                Tile::Wan(TileValue::Five),
+                Tile::Wan(TileValue::Six),
+                Tile::Wan(TileValue::Eight),
+                Tile::Wan(TileValue::Eight),
+                Tile::Dragon(Dragon::Fa),
+                Tile::Dragon(Dragon::Fa),
+                Tile::Dragon(Dragon::Fa),
+                Tile::Wind(Wind::North),
+                Tile::Wind(Wind::North),
+                Tile::Flower(Flower::Blue(FlowerValue::Two)),
+            ],
+        );
+        let ctx = WinContext::discard(Tile::Wind(Wind::North), Wind::East, Wind::South, Wind::East);
+        let hu = HongKong::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![
+                Fan::Concealed,
+                Fan::SeatFlower,
+                Fan::DragonPong,
+                Fan::MixedOneSuit
+            ]
+        );
+        assert_eq!(hu.fan(), 6);
+    }
+
+    #[test]
+    fn test_below_minimum() {
+        let mut hand = Hand::new();
+        draw_all(
+            &mut hand,
+            &[Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
+        );
+        hand.meld(
+            Meld::new(
+                vec![Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
+                Some(Tile::Wan(TileValue::Four)),
+                MeldType::Chi,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Suo(TileValue::Two),
+                Tile::Suo(TileValue::Three),
+                Tile::Suo(TileValue::Four),
+                Tile::Tong(TileValue::Six),
+                Tile::Tong(TileValue::Seven),
+                Tile::Tong(TileValue::Eight),
+                Tile::Tong(TileValue::Five),
+                Tile::Tong(TileValue::Five),
+                Tile::Tong(TileValue::Five),
+                Tile::Suo(TileValue::Nine),
+                Tile::Flower(Flower::Red(FlowerValue::Three)),
+            ],
+        );
+        let ctx = WinContext::discard(
+            Tile::Suo(TileValue::Nine),
+            Wind::East,
+            Wind::South,
+            Wind::East,
+        );
+        let rules = HongKong::default();
+        assert_eq!(rules.search_hus(&hand, &ctx)[0].fan(), 0);
+        assert_eq!(rules.search_hu(&hand, &ctx), None);
+        assert_eq!(rules.score(&hand, &ctx), None);
+    }
+
+    #[test]
+    fn test_eight_flowers_limit() {
+        let mut hand = Hand::new();
+        for value in [
+            FlowerValue::One,
+            FlowerValue::Two,
+            FlowerValue::Three,
+            FlowerValue::Four,
+        ] {
+            hand.draw(&Tile::Flower(Flower::Red(value)));
+            hand.draw(&Tile::Flower(Flower::Blue(value)));
+        }
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Wan(TileValue::One),
+                Tile::Wan(TileValue::Two),
+                Tile::Wan(TileValue::Three),
+                Tile::Suo(TileValue::Four),
+                Tile::Suo(TileValue::Five),
+                Tile::Suo(TileValue::Six),
+                Tile::Tong(TileValue::Seven),
+                Tile::Tong(TileValue::Eight),
+                Tile::Tong(TileValue::Nine),
+                Tile::Tong(TileValue::Two),
+                Tile::Tong(TileValue::Three),
+                Tile::Tong(TileValue::Four),
+                Tile::Wan(TileValue::Nine),
+                Tile::Wan(TileValue::Nine),
+            ],
+        );
+        let ctx = WinContext::self_drawn(Tile::Wan(TileValue::Nine), Wind::East, Wind::East);
+        let hu = HongKong::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(hu.fans(), &vec![Fan::EightFlowers]);
+        assert_eq!(hu.fan(), 10);
+    }
+
+    #[test]
+    fn test_limit_fans_deduplicated() {
+        let fans = vec![
+            Fan::AllHonours,
+            Fan::SelfDrawn,
+            Fan::EightFlowers,
+            Fan::AllHonours,
+        ];
+        let hu = HongKong::default().new_hu(Vec::new(), fans);
+        assert_eq!(hu.fans(), &vec![Fan::AllHonours, Fan::EightFlowers]);
+        assert_eq!(hu.fan(), 10);
+    }
+
+    #[test]
+    fn test_flower_set() {
+        let mut hand = Hand::new();
+        for value in [
+            FlowerValue::One,
+            FlowerValue::Two,
+            FlowerValue::Three,
+            FlowerValue::Four,
+        ] {
+            hand.draw(&Tile::Flower(Flower::Red(value)));
+        }
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Wan(TileValue::One),
+                Tile::Wan(TileValue::Two),
+                Tile::Wan(TileValue::Three),
+                Tile::Suo(TileValue::Four),
+                Tile::Suo(TileValue::Five),
+                Tile::Suo(TileValue::Six),
+                Tile::Tong(TileValue::Seven),
+                Tile::Tong(TileValue::Eight),
+                Tile::Tong(TileValue::Nine),
+                Tile::Tong(TileValue::Two),
+                Tile::Tong(TileValue::Three),
+                Tile::Tong(TileValue::Four),
+                Tile::Wan(TileValue::Nine),
+                Tile::Wan(TileValue::Nine),
+            ],
+        );
+        let ctx = WinContext::self_drawn(Tile::Wan(TileValue::Nine), Wind::West, Wind::East);
+        let hu = HongKong::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![
+                Fan::SelfDrawn,
+                Fan::Concealed,
+                Fan::SeatFlower,
+                Fan::FlowerSet,
+                Fan::AllChows
+            ]
+        );
+        assert_eq!(hu.fan(), 5);
+    }
+
+    #[test]
+    fn test_payment_doubling() {
+        let rules = HongKong::default();
+        assert_eq!(rules.points(3).unwrap(), 8);
+        assert_eq!(rules.points(13).unwrap(), 1024);
+        let house = HongKong {
+            limit: 40,
+            ..HongKong::default()
+        };
+        assert_eq!(house.points(31).unwrap(), 1 << 31);
+        assert_matches!(
+            house.points(32),
+            Err(MahjongError::PointsOverflowError(1, 32))
+        );
+
+        let mut hand = Hand::new();
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Dragon(Dragon::Zhong),
+                Tile::Dragon(Dragon::Zhong),
+                Tile::Dragon(Dragon::Zhong),
+                Tile::Dragon(Dragon::Fa),
+                Tile::Dragon(Dragon::Fa),
+                Tile::Dragon(Dragon::Fa),
+                Tile::Tong(TileValue::One),
+                Tile::Tong(TileValue::Two),
+                Tile::Tong(TileValue::Three),
+                Tile::Tong(TileValue::Seven),
+                Tile::Tong(TileValue::Eight),
+                Tile::Tong(TileValue::Nine),
+                Tile::Tong(TileValue::Five),
+            ],
+        );
+        let ctx = WinContext::discard(
+            Tile::Tong(TileValue::Five),
+            Wind::West,
+            Wind::North,
+            Wind::East,
+        );
+        let hu = rules.search_hu(&hand, &ctx).unwrap();
+        // Concealed, NoFlowers, two DragonPong, MixedOneSuit
+        assert_eq!(hu.fan(), 7);
+        assert_eq!(
+            rules.payments(&hu, &ctx).unwrap(),
+            vec![(Wind::East, 128), (Wind::South, 128), (Wind::West, 256)]
+        );
+
+        let ctx = WinContext::self_drawn(Tile::Tong(TileValue::Five), Wind::North, Wind::East);
+        hand.draw(&Tile::Tong(TileValue::Five));
+        let hu = rules.search_hu(&hand, &ctx).unwrap();
+        assert_eq!(hu.fan(), 8);
+        assert!(rules
+            .payments(&hu, &ctx)
+            .unwrap()
+            .iter()
+            .all(|(_, p)| *p == 512));
+        let house = HongKong {
+            base: u32::MAX / 256,
+            ..HongKong::default()
+        };
+        assert_err!(house.payments(&hu, &ctx));
+    }
+}
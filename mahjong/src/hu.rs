@@ -1,96 +1,216 @@
-use std::{
-    collections::{hash_map, HashMap},
-    str::Matches,
-};
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    context::WinContext,
     hand::{ConcealedTiles, Hand},
     meld::{Meld, MeldType},
-    tile::{Flower, FlowerValue, Suit, Tile, TileValue, Wind},
+    tai::ScoreTai,
+    tile::{FlowerColour, FlowerValue, Suit, Tile, TileValue, Wind},
 };
 
-pub type ScoreTai = HashMap<Score, u8>;
-
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Hu {
     melds: Vec<Meld>,
     scores: Vec<Score>,
     tai: u8,
+    waits: Vec<Wait>,
 }
 
 impl Hu {
-    pub fn new(melds: Vec<Meld>, scores: Vec<Score>, score_tai: &ScoreTai) -> Self {
-        let tai = scores
-            .iter()
-            .map(|s| score_tai.get(s).unwrap_or(&0_u8))
-            .sum();
-        Self { melds, scores, tai }
+    pub fn new(mut melds: Vec<Meld>, scores: Vec<Score>, score_tai: &ScoreTai) -> Self {
+        let (mut scores, tai) = score_tai.resolve(scores);
+        melds.sort();
+        scores.sort();
+        Self {
+            melds,
+            scores,
+            tai,
+            waits: Vec::new(),
+        }
+    }
+
+    // Every way the winning tile can be read into the concealed sets of this hand
+    pub(crate) fn with_waits(mut self, waits: Vec<Wait>) -> Self {
+        self.waits = waits;
+        self
+    }
+
+    pub fn num_limits(&self) -> usize {
+        self.scores.iter().filter(|s| s.is_limit()).count()
+    }
+
+    pub fn num_concealed_sets(&self) -> usize {
+        self.melds.iter().filter(|m| m.is_concealed_set()).count()
+    }
+
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn scores(&self) -> &Vec<Score> {
+        &self.scores
+    }
+
+    pub fn tai(&self) -> u8 {
+        self.tai
+    }
+
+    pub fn waits(&self) -> &Vec<Wait> {
+        &self.waits
     }
 }
 
+// A greater Hu is a better interpretation: more tai, then fewer limit scores, then more
+// concealed sets. Remaining ties fall back to the melds and scores themselves.
 impl std::cmp::Ord for Hu {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.tai.cmp(&other.tai)
+        self.tai
+            .cmp(&other.tai)
+            .then_with(|| other.num_limits().cmp(&self.num_limits()))
+            .then_with(|| self.num_concealed_sets().cmp(&other.num_concealed_sets()))
+            .then_with(|| other.melds.cmp(&self.melds))
+            .then_with(|| other.scores.cmp(&self.scores))
     }
 }
 
-impl std::cmp::Eq for Hu {}
-
-impl std::cmp::PartialEq for Hu {
-    fn eq(&self, other: &Self) -> bool {
-        self.tai == other.tai
+impl std::cmp::PartialOrd for Hu {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
-impl std::cmp::PartialOrd for Hu {
-    fn gt(&self, other: &Self) -> bool {
-        self.tai > other.tai
+// The number of sets a winning hand needs besides its eye
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandShape {
+    pub sets: usize,
+}
+
+impl HandShape {
+    pub const STANDARD: HandShape = HandShape { sets: 4 };
+    pub const SIXTEEN_TILE: HandShape = HandShape { sets: 5 };
+
+    // Concealed tiles held between turns, before kong replacements
+    pub fn hand_size(&self) -> usize {
+        self.sets * 3 + 1
     }
 
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.tai.cmp(&other.tai))
+    // Sets still to be formed from the concealed tiles
+    pub fn concealed_sets(&self, hand: &Hand) -> usize {
+        self.sets.saturating_sub(hand.melds().len())
     }
 }
 
-impl std::cmp::PartialEq<Option<Hu>> for Hu {
-    fn eq(&self, other: &Option<Hu>) -> bool {
-        match other {
-            Some(o) => self.eq(o),
-            None => false,
+// How the winning tile completed a set of the hand
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wait {
+    // 12 waiting on 3, or 89 waiting on 7
+    Edge,
+    // 13 waiting on 2
+    Closed,
+    // A lone tile waiting on its pair
+    SingleEye,
+    // 23 waiting on 1 or 4
+    TwoSided,
+    // Two pairs waiting on either to become a pong
+    DualPong,
+}
+
+// The wait filled by the winning tile in a set, or None if the set does not hold the tile
+pub fn classify_wait(meld: &Meld, winning_tile: &Tile) -> Option<Wait> {
+    let winning_tile = winning_tile.normalized();
+    let tiles: Vec<Tile> = meld.all_tiles().iter().map(|t| t.normalized()).collect();
+    if !tiles.contains(&winning_tile) {
+        return None;
+    }
+    match meld.meld_type() {
+        MeldType::Eye => Some(Wait::SingleEye),
+        MeldType::Chi => {
+            if tiles[1] == winning_tile {
+                Some(Wait::Closed)
+            } else if (tiles[0] == winning_tile && tiles[2].is_terminal())
+                || (tiles[2] == winning_tile && tiles[0].is_terminal())
+            {
+                Some(Wait::Edge)
+            } else {
+                Some(Wait::TwoSided)
+            }
         }
+        _ => Some(Wait::DualPong),
     }
 }
 
-impl std::cmp::PartialOrd<Option<Hu>> for Hu {
-    fn partial_cmp(&self, other: &Option<Hu>) -> Option<std::cmp::Ordering> {
-        match other {
-            Some(o) => self.partial_cmp(o),
-            None => Some(std::cmp::Ordering::Less),
+// Each distinct wait the winning tile could have filled among the concealed sets
+pub(crate) fn classify_waits(melds: &[Meld], winning_tile: &Tile) -> Vec<Wait> {
+    let mut waits: Vec<Wait> = melds
+        .iter()
+        .filter_map(|m| classify_wait(m, winning_tile))
+        .collect();
+    waits.sort();
+    waits.dedup();
+    waits
+}
+
+pub(crate) fn is_thirteen_wonders(concealed: &ConcealedTiles) -> bool {
+    concealed.len() == 13
+        && concealed.values().sum::<u8>() == 14
+        && Tile::terminals_and_honours()
+            .iter()
+            .all(|t| concealed.contains_key(t))
+}
+
+// 1112345678999 in one suit plus any tile of the same suit
+pub(crate) fn is_nine_gates(concealed: &ConcealedTiles) -> bool {
+    let counts: HashMap<TileValue, u8> = concealed
+        .iter()
+        .filter_map(|(t, c)| match t {
+            Tile::Wan(v) | Tile::Suo(v) | Tile::Tong(v) => Some((*v, *c)),
+            _ => None,
+        })
+        .collect();
+    let mut value = Some(TileValue::One);
+    while let Some(v) = value {
+        let needed = match v {
+            TileValue::One | TileValue::Nine => 3,
+            _ => 1,
+        };
+        if counts.get(&v).unwrap_or(&0) < &needed {
+            return false;
         }
+        value = v.next();
     }
+    concealed.values().sum::<u8>() == 14
 }
 
-fn search_melds(concealed: &ConcealedTiles) -> Vec<Vec<Meld>> {
+// Splits the concealed tiles into an eye followed by exactly `sets` sets
+pub(crate) fn search_melds(concealed: &ConcealedTiles, sets: usize) -> Vec<Vec<Meld>> {
     let mut poss_melds = Vec::new();
     let mut search: Vec<(ConcealedTiles, Vec<Meld>)> = Vec::from([(concealed.clone(), Vec::new())]);
 
     while let Some((cur_concealed, cur_melds)) = search.pop() {
         if cur_concealed.is_empty() {
-            poss_melds.push(cur_melds);
+            if cur_melds.len() == sets + 1 {
+                poss_melds.push(cur_melds);
+            }
+            continue;
+        }
+        if cur_melds.len() > sets {
             continue;
         }
 
         if cur_melds.is_empty() {
             // Search for eyes
             for (tile, count) in cur_concealed.iter() {
+                // Jokers never form part of an eye
+                let Ok(eye) = Meld::new(vec![tile.to_owned(); 2], None, MeldType::Eye) else {
+                    continue;
+                };
                 if count >= &2 {
                     let mut next_hand = cur_concealed.clone();
                     let mut next_melds = cur_melds.clone();
                     next_hand.remove_n(tile, 2).unwrap();
-                    next_melds.push(
-                        Meld::new(vec![tile.to_owned(); 2], None, crate::meld::MeldType::Eye)
-                            .unwrap(),
-                    );
+                    next_melds.push(eye);
                     search.push((next_hand, next_melds));
                 }
             }
@@ -98,13 +218,13 @@ fn search_melds(concealed: &ConcealedTiles) -> Vec<Vec<Meld>> {
             let (tile, count) = cur_concealed.iter().next().unwrap();
             // Search for pong
             if count >= &3 {
-                let mut next_hand = cur_concealed.clone();
-                let mut next_melds = cur_melds.clone();
-                next_hand.remove_n(tile, 3).unwrap();
-                next_melds.push(
-                    Meld::new(vec![tile.to_owned(); 3], None, crate::meld::MeldType::Pong).unwrap(),
-                );
-                search.push((next_hand, next_melds));
+                if let Ok(pong) = Meld::new(vec![tile.to_owned(); 3], None, MeldType::Pong) {
+                    let mut next_hand = cur_concealed.clone();
+                    let mut next_melds = cur_melds.clone();
+                    next_hand.remove_n(tile, 3).unwrap();
+                    next_melds.push(pong);
+                    search.push((next_hand, next_melds));
+                }
             }
 
             let prev = tile.prev();
@@ -112,8 +232,10 @@ fn search_melds(concealed: &ConcealedTiles) -> Vec<Vec<Meld>> {
             let next = tile.next();
             let next_next = next.and_then(|t| t.next());
             let tiles_to_check = [prev_prev, prev, next, next_next];
-            let mut it = tiles_to_check.windows(2);
-            while let Some([Some(t1), Some(t2)]) = it.next() {
+            for window in tiles_to_check.windows(2) {
+                let [Some(t1), Some(t2)] = window else {
+                    continue;
+                };
                 if !(cur_concealed.contains_key(t1) && cur_concealed.contains_key(t2)) {
                     continue;
                 }
@@ -123,7 +245,7 @@ fn search_melds(concealed: &ConcealedTiles) -> Vec<Vec<Meld>> {
                 next_hand.remove_n(t2, 1).unwrap();
                 next_hand.remove_n(tile, 1).unwrap();
                 let chi_tiles = vec![*tile, *t1, *t2];
-                next_melds.push(Meld::new(chi_tiles, None, crate::meld::MeldType::Chi).unwrap());
+                next_melds.push(Meld::new(chi_tiles, None, MeldType::Chi).unwrap());
                 search.push((next_hand, next_melds));
             }
         }
@@ -142,91 +264,72 @@ impl std::cmp::PartialEq<FlowerValue> for Wind {
     }
 }
 
-pub fn search_hu(
-    hand: &Hand,
-    discarded_tile: Option<&Tile>,
-    scores: Vec<Score>,
-    seat_wind: &Wind,
-    prevailing_wind: &Wind,
-    score_tai: &ScoreTai,
-) -> Option<Hu> {
+pub fn search_hu(hand: &Hand, ctx: &WinContext, score_tai: &ScoreTai) -> Option<Hu> {
+    search_hus(hand, ctx, score_tai).into_iter().next()
+}
+
+// Every valid interpretation of the hand, best first
+pub fn search_hus(hand: &Hand, ctx: &WinContext, score_tai: &ScoreTai) -> Vec<Hu> {
+    let seat_wind = &ctx.seat_wind;
+    let prevailing_wind = &ctx.prevailing_wind;
+    let discarded_tile = ctx.claimed_tile();
     let mut concealed = hand.concealed().clone();
     if let Some(tile) = discarded_tile {
         concealed.add_n(tile, 1);
     }
-    let mut all_scores = scores.clone();
-    // [Animal, RedFlower, BlueFlower]
-    let mut bonus_tiles = [0_u8; 3];
-
-    for bonus_tile in hand.bonus() {
-        match bonus_tile {
-            Tile::Animal(_) => {
-                all_scores.push(Score::Animal);
-                bonus_tiles[0] += 1;
-            }
-            Tile::Flower(Flower::Red(f)) => {
-                if seat_wind == f {
-                    all_scores.push(Score::PlayerFlower);
-                }
-                bonus_tiles[1] += 1;
-            }
-            Tile::Flower(Flower::Blue(f)) => {
-                if seat_wind == f {
-                    all_scores.push(Score::PlayerFlower);
-                }
-                bonus_tiles[2] += 1;
-            }
-            _ => unreachable!(),
-        }
-    }
-
-    // CompleteAnimals
-    if bonus_tiles[0] == 4 {
+    let mut all_scores = ctx.situational_scores();
+    let bonus = hand.bonus();
+    all_scores.extend(bonus.animals().map(|_| Score::Animal));
+    all_scores.extend(
+        bonus
+            .seat_flowers(*seat_wind)
+            .iter()
+            .map(|_| Score::PlayerFlower),
+    );
+    if bonus.has_all_animals() {
         all_scores.push(Score::CompleteAnimals);
     }
-
-    // Complete Flowers
-    if bonus_tiles[1] == 4 {
-        all_scores.push(Score::CompleteRedFlower);
-    }
-    if bonus_tiles[2] == 4 {
-        all_scores.push(Score::CompleteBlueFlower)
+    for colour in bonus.complete_colours() {
+        all_scores.push(match colour {
+            FlowerColour::Red => Score::CompleteRedFlower,
+            FlowerColour::Blue => Score::CompleteBlueFlower,
+        });
     }
 
-    let mut best_hu = None;
+    let mut hus = Vec::new();
 
-    // TODO check SevenPairs
     if hand.melds().is_empty() {
-        // Check ThirteenWonders
-        let mut all_pairs = true;
-        for (tile, count) in hand.concealed().iter() {
-            if count != &2 || count != &4 {
-                all_pairs = false;
-            }
-        }
-        if all_pairs {
+        // Check SevenPairs
+        if concealed.values().all(|c| c % 2 == 0) && concealed.values().sum::<u8>() == 14 {
             let mut scores_with_all_pairs = all_scores.clone();
             scores_with_all_pairs.push(Score::SevenPairs);
 
-            best_hu = Some(Hu::new(
-                hand.concealed()
-                    .keys()
-                    .map(|t| Meld::new(vec![*t; 2], None, MeldType::Eye).unwrap())
-                    .collect(),
-                scores_with_all_pairs,
-                score_tai,
-            ))
+            let pairs: Vec<Meld> = concealed
+                .iter()
+                .flat_map(|(t, c)| {
+                    vec![Meld::new(vec![*t; 2], None, MeldType::Eye).unwrap(); *c as usize / 2]
+                })
+                .collect();
+            let waits = classify_waits(&pairs, &ctx.winning_tile);
+            hus.push(Hu::new(pairs, scores_with_all_pairs, score_tai).with_waits(waits))
+        }
+        // Check ThirteenWonders
+        if is_thirteen_wonders(&concealed) {
+            let mut scores_with_wonders = all_scores.clone();
+            scores_with_wonders.push(Score::ThirteenWonders);
+            hus.push(Hu::new(Vec::new(), scores_with_wonders, score_tai));
         }
-        // [1Wan, 9Wan, 1Suo, 9Suo, 1Tong, 9Tong, Zhong, Fa, Baiban, East, South, West, North]
     }
 
     // TODO check ThreeGreatScholars
     // TODO check FourGreatBlessings
 
-    let mut poss_melds = search_melds(&concealed);
+    let shape = HandShape::STANDARD;
+    let mut poss_melds = search_melds(&concealed, shape.concealed_sets(hand));
 
     for poss_meld in poss_melds.iter_mut() {
-        let mut cur_scores = scores.clone();
+        let mut cur_scores = all_scores.clone();
+        let waits = classify_waits(poss_meld, &ctx.winning_tile);
         let mut all_melds = hand.melds().clone();
         all_melds.append(poss_meld);
 
@@ -236,6 +339,8 @@ pub fn search_hu(
         let mut meld_types = [0_u8; 5];
 
         for meld in all_melds.iter() {
+            // A pair of dragons or winds scores nothing, only their sets do
+            let is_eye = meld.meld_type().eq(&MeldType::Eye);
             match meld.suit() {
                 Suit::Wan => suits[0] += 1,
                 Suit::Suo => suits[1] += 1,
@@ -244,6 +349,7 @@ pub fn search_hu(
                     suits[3] += 1;
                     // Check Wind
                     match meld.tiles().first() {
+                        Some(Tile::Wind(_)) if is_eye => (),
                         Some(Tile::Wind(w)) => {
                             if w == prevailing_wind {
                                 cur_scores.push(Score::PrevailingWind)
@@ -257,7 +363,9 @@ pub fn search_hu(
                 }
                 Suit::Dragon => {
                     suits[4] += 1;
-                    cur_scores.push(Score::Dragon);
+                    if !is_eye {
+                        cur_scores.push(Score::Dragon);
+                    }
                 }
                 _ => unreachable!(),
             }
@@ -271,7 +379,8 @@ pub fn search_hu(
         }
 
         // Check suits
-        let num_number_suits: u8 = suits[0..4].iter().sum();
+        // Wan, suo and tong each count once however many sets they make
+        let num_number_suits = suits[0..3].iter().filter(|c| **c > 0).count();
         if num_number_suits == 1 {
             match suits[3] > 0 || suits[4] > 0 {
                 true => cur_scores.push(Score::HalfFlush),
@@ -284,35 +393,14 @@ pub fn search_hu(
         }
 
         // Check all Chi
-        if meld_types[0] == 4 {
-            let mut two_side_wait = false;
-            let mut no_scoring_eyes = true;
-            if let Some(dt) = discarded_tile {
-                for meld in all_melds.iter() {
-                    if meld.meld_type().eq(&MeldType::Chi) && meld.tiles().contains(dt) {
-                        if let (Some(_), Some(_)) = (dt.prev(), dt.prev().and_then(|t| t.prev())) {
-                            two_side_wait = true;
-                        }
-                        if let (Some(_), Some(_)) = (dt.next(), dt.next().and_then(|t| t.next())) {
-                            two_side_wait = true;
-                        }
-                    } else if meld.meld_type().eq(&MeldType::Eye)
-                        && (meld.suit().eq(&Suit::Dragon)
-                            || meld
-                                .tiles()
-                                .first()
-                                .unwrap()
-                                .eq(&Tile::Wind(prevailing_wind.to_owned()))
-                            || meld
-                                .tiles()
-                                .first()
-                                .unwrap()
-                                .eq(&Tile::Wind(seat_wind.to_owned())))
-                    {
-                        no_scoring_eyes = false;
-                    }
-                }
-            }
+        if meld_types[0] as usize == shape.sets {
+            let no_scoring_eyes = all_melds.iter().all(|meld| {
+                !meld.meld_type().eq(&MeldType::Eye)
+                    || !(meld.suit().eq(&Suit::Dragon)
+                        || meld.tiles()[0].eq(&Tile::Wind(prevailing_wind.to_owned()))
+                        || meld.tiles()[0].eq(&Tile::Wind(seat_wind.to_owned())))
+            });
+            let two_side_wait = waits.contains(&Wait::TwoSided);
             if discarded_tile.is_none() || (two_side_wait && no_scoring_eyes) {
                 match hand.bonus().len() {
                     0 => cur_scores.push(Score::PingHu),
@@ -329,11 +417,11 @@ pub fn search_hu(
         // Special Hands (Limit)
         // HiddenTreasure
         if hand.melds().is_empty() && meld_types[0] == 0 {
-            cur_scores = vec![Score::HiddenTreasure];
+            cur_scores.push(Score::HiddenTreasure);
         }
         // AllHonours
         if num_number_suits == 0 {
-            cur_scores = vec![Score::AllHonours];
+            cur_scores.push(Score::AllHonours);
         }
         // AllTerminals
         if all_melds.iter().all(|m| {
@@ -349,24 +437,23 @@ pub fn search_hu(
                     _ => false,
                 }
         }) {
-            cur_scores = vec![Score::AllTerminals];
+            cur_scores.push(Score::AllTerminals);
         }
 
         // FullFlushPingHu
-        if meld_types[0] == 4 && num_number_suits == 1 {
-            cur_scores = vec![Score::FullFlushPingHu];
+        if cur_scores.contains(&Score::FullFlush) && cur_scores.contains(&Score::PingHu) {
+            cur_scores.push(Score::FullFlushPingHu);
         }
 
-        let hu = Some(Hu::new(all_melds, cur_scores, score_tai));
-        if hu > best_hu {
-            best_hu = hu;
-        }
+        hus.push(Hu::new(all_melds, cur_scores, score_tai).with_waits(waits));
     }
 
-    best_hu
+    hus.sort_by(|a, b| b.cmp(a));
+    hus.dedup();
+    hus
 }
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, PartialOrd, Ord, Eq)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, PartialOrd, Ord, Eq, Serialize, Deserialize)]
 pub enum Score {
     Dragon,          // ok
     PrevailingWind,  // ok
@@ -398,19 +485,307 @@ pub enum Score {
     FourGreatBlessings,
     XiaoSiXi,
     SevenPairs,
+    DealerStreak,
+}
+
+impl Score {
+    pub const ALL: [Score; 31] = [
+        Score::Dragon,
+        Score::PrevailingWind,
+        Score::SeatWind,
+        Score::AllConcealed,
+        Score::AllChi,
+        Score::PingHu,
+        Score::AllPong,
+        Score::HiddenTreasure,
+        Score::HalfFlush,
+        Score::FullFlush,
+        Score::FullFlushPingHu,
+        Score::AllTerminals,
+        Score::HalfTerminals,
+        Score::AllHonours,
+        Score::ThirteenWonders,
+        Score::Animal,
+        Score::CompleteAnimals,
+        Score::PlayerFlower,
+        Score::CompleteRedFlower,
+        Score::CompleteBlueFlower,
+        Score::HuaShang,
+        Score::GangShang,
+        Score::HaiDiLao,
+        Score::QiangGang,
+        Score::HuaHu,
+        Score::ThreeGreatScholars,
+        Score::DaSiXi,
+        Score::FourGreatBlessings,
+        Score::XiaoSiXi,
+        Score::SevenPairs,
+        Score::DealerStreak,
+    ];
+
+    pub fn is_limit(&self) -> bool {
+        matches!(
+            self,
+            Score::HiddenTreasure
+                | Score::FullFlushPingHu
+                | Score::AllTerminals
+                | Score::AllHonours
+                | Score::ThirteenWonders
+                | Score::HuaHu
+                | Score::ThreeGreatScholars
+                | Score::DaSiXi
+                | Score::FourGreatBlessings
+                | Score::XiaoSiXi
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
-
     use crate::{
+        context::{Replacement, WinContext},
+        corpus::parse_hand,
         hand::Hand,
-        hu::{search_hu, Hu, Score},
+        hu::{classify_wait, search_hu, search_hus, Hu, Score, Wait},
         meld::{Meld, MeldType},
-        tile::{Animal, Tile, TileValue, Wind},
+        tai::ScoreTai,
+        tile::{Animal, Dragon, Flower, FlowerValue, Tile, TileValue, Wind},
     };
 
+    #[test]
+    fn test_search_hu_limit_keeps_bonus_scores() {
+        let mut hand = Hand::new();
+        for tile in [
+            Tile::Wan(TileValue::One),
+            Tile::Suo(TileValue::Five),
+            Tile::Tong(TileValue::Nine),
+            Tile::Dragon(Dragon::Zhong),
+        ] {
+            for _ in 0..3 {
+                hand.draw(&tile);
+            }
+        }
+        hand.draw(&Tile::Wan(TileValue::Two));
+        hand.draw(&Tile::Animal(Animal::Cat));
+        hand.draw(&Tile::Flower(Flower::Red(FlowerValue::One)));
+
+        let found_hu = search_hu(
+            &hand,
+            &WinContext::discard(
+                Tile::Wan(TileValue::Two),
+                Wind::North,
+                Wind::East,
+                Wind::South,
+            ),
+            &ScoreTai::singapore(),
+        )
+        .unwrap();
+        let mut scores = found_hu.scores().clone();
+        scores[1..].sort();
+        assert_eq!(
+            scores,
+            vec![Score::HiddenTreasure, Score::Animal, Score::PlayerFlower]
+        );
+        assert_eq!(found_hu.tai(), 5);
+    }
+
+    #[test]
+    fn test_search_hu_situational_scores() {
+        let mut hand = Hand::new();
+        for _ in 0..2 {
+            hand.draw(&Tile::Dragon(Dragon::Zhong));
+        }
+        hand.meld(
+            Meld::new(
+                vec![Tile::Dragon(Dragon::Zhong); 2],
+                Some(Tile::Dragon(Dragon::Zhong)),
+                MeldType::Pong,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        for value in [TileValue::One, TileValue::Two, TileValue::Three] {
+            hand.draw(&Tile::Wan(value));
+        }
+        for value in [TileValue::Four, TileValue::Five, TileValue::Six] {
+            hand.draw(&Tile::Suo(value));
+        }
+        for value in [TileValue::Seven, TileValue::Eight, TileValue::Nine] {
+            hand.draw(&Tile::Tong(value));
+        }
+        hand.draw(&Tile::Wan(TileValue::Five));
+        hand.draw(&Tile::Wan(TileValue::Five));
+
+        let ctx = WinContext::self_drawn(Tile::Wan(TileValue::Five), Wind::South, Wind::East)
+            .with_replacement(Replacement::Kong);
+        let found_hu = search_hu(&hand, &ctx, &ScoreTai::singapore()).unwrap();
+        let mut scores = found_hu.scores().clone();
+        scores.sort();
+        assert_eq!(scores, vec![Score::Dragon, Score::GangShang]);
+        assert_eq!(found_hu.tai(), 2);
+    }
+
+    #[test]
+    fn test_search_hu_honour_eyes_do_not_score() {
+        let score_tai = ScoreTai::singapore();
+        let hand = parse_hand("123w 456s 789t 234t 1z").unwrap();
+        let ctx = WinContext::discard(Tile::Wind(Wind::East), Wind::North, Wind::East, Wind::East);
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert_eq!(found_hu.scores(), &vec![Score::AllConcealed]);
+
+        let hand = parse_hand("123w 456s 789t 234t 5z").unwrap();
+        let ctx = WinContext::discard(
+            Tile::Dragon(Dragon::Zhong),
+            Wind::North,
+            Wind::East,
+            Wind::East,
+        );
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert_eq!(found_hu.scores(), &vec![Score::AllConcealed]);
+        assert_eq!(found_hu.tai(), 1);
+    }
+
+    #[test]
+    fn test_search_hu_flush_counts_suits() {
+        let score_tai = ScoreTai::singapore();
+        let hand = parse_hand("[666z] 123t 456t 78t 11z").unwrap();
+        let ctx = WinContext::discard(
+            Tile::Tong(TileValue::Nine),
+            Wind::West,
+            Wind::South,
+            Wind::East,
+        );
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert!(found_hu.scores().contains(&Score::HalfFlush));
+
+        let hand = parse_hand("[111t] 456t 789t 234t 5t").unwrap();
+        let ctx = WinContext::discard(
+            Tile::Tong(TileValue::Five),
+            Wind::West,
+            Wind::South,
+            Wind::East,
+        );
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert!(found_hu.scores().contains(&Score::FullFlush));
+
+        let hand = parse_hand("[111w] 456t 789t 234t 5t").unwrap();
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert!(!found_hu
+            .scores()
+            .iter()
+            .any(|s| matches!(s, Score::HalfFlush | Score::FullFlush)));
+    }
+
+    #[test]
+    fn test_search_hu_full_flush_ping_hu_needs_ping_hu() {
+        let score_tai = ScoreTai::singapore();
+        let hand = parse_hand("[123t] 456t 789t 55t 23t").unwrap();
+        let ctx = WinContext::discard(
+            Tile::Tong(TileValue::One),
+            Wind::West,
+            Wind::South,
+            Wind::East,
+        );
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert!(found_hu.scores().contains(&Score::FullFlushPingHu));
+
+        let hand = parse_hand("[123t] 456t 789t 55t 12t").unwrap();
+        let ctx = WinContext::discard(
+            Tile::Tong(TileValue::Three),
+            Wind::West,
+            Wind::South,
+            Wind::East,
+        );
+        let found_hu = search_hu(&hand, &ctx, &score_tai).unwrap();
+        assert!(found_hu.scores().contains(&Score::FullFlush));
+        assert!(!found_hu.scores().contains(&Score::FullFlushPingHu));
+    }
+
+    #[test]
+    fn test_search_hus_ranked() {
+        let mut hand = Hand::new();
+        for value in [TileValue::One, TileValue::Two, TileValue::Three] {
+            for _ in 0..3 {
+                hand.draw(&Tile::Wan(value));
+            }
+        }
+        for value in [TileValue::Seven, TileValue::Eight, TileValue::Nine] {
+            hand.draw(&Tile::Tong(value));
+        }
+        hand.draw(&Tile::Suo(TileValue::Five));
+
+        let ctx = WinContext::self_drawn(Tile::Suo(TileValue::Five), Wind::South, Wind::East);
+        let hus = search_hus(&hand, &ctx, &ScoreTai::singapore());
+        assert_eq!(hus.len(), 0);
+
+        hand.draw(&Tile::Suo(TileValue::Five));
+        let hus = search_hus(&hand, &ctx, &ScoreTai::singapore());
+        // Three pongs or three identical chis
+        assert_eq!(hus.len(), 2);
+        assert!(hus.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(
+            search_hu(&hand, &ctx, &ScoreTai::singapore()),
+            Some(hus[0].clone())
+        );
+        assert!(hus[0].scores().contains(&Score::PingHu));
+        assert!(hus[1].scores().contains(&Score::AllConcealed));
+    }
+
+    #[test]
+    fn test_hu_tie_break() {
+        let score_tai = ScoreTai::singapore();
+        let chi = |tiles: Vec<TileValue>, discarded_tile| {
+            Meld::new(
+                tiles.into_iter().map(Tile::Wan).collect(),
+                discarded_tile,
+                MeldType::Chi,
+            )
+            .unwrap()
+        };
+        let concealed = Hu::new(
+            vec![chi(
+                vec![TileValue::One, TileValue::Two, TileValue::Three],
+                None,
+            )],
+            vec![Score::Dragon],
+            &score_tai,
+        );
+        let exposed = Hu::new(
+            vec![chi(
+                vec![TileValue::Two, TileValue::Three],
+                Some(Tile::Wan(TileValue::One)),
+            )],
+            vec![Score::Dragon],
+            &score_tai,
+        );
+        assert!(concealed > exposed);
+        assert_ne!(concealed, exposed);
+
+        let limit = Hu::new(vec![], vec![Score::AllHonours], &score_tai);
+        let capped = Hu::new(vec![], vec![Score::FullFlush, Score::AllPong], &score_tai);
+        assert_eq!(limit.tai(), capped.tai());
+        assert!(capped > limit);
+    }
+
+    #[test]
+    fn test_hu_structural_eq() {
+        let score_tai = ScoreTai::singapore();
+        let eye = Meld::new(vec![Tile::Wan(TileValue::One); 2], None, MeldType::Eye).unwrap();
+        let pong = Meld::new(vec![Tile::Wan(TileValue::Two); 3], None, MeldType::Pong).unwrap();
+        assert_eq!(
+            Hu::new(
+                vec![eye.clone(), pong.clone()],
+                vec![Score::Dragon, Score::Animal],
+                &score_tai
+            ),
+            Hu::new(
+                vec![pong, eye],
+                vec![Score::Animal, Score::Dragon],
+                &score_tai
+            )
+        );
+    }
+
     #[test]
     fn test_search_hu_pinghu() {
         let mut hand = Hand::new();
@@ -450,29 +825,53 @@ mod tests {
 
         let found_hu = search_hu(
             &hand,
-            Some(&Tile::Wan(TileValue::Three)),
-            Vec::new(),
-            &Wind::South,
-            &Wind::South,
-            &HashMap::new(),
+            &WinContext::discard(
+                Tile::Wan(TileValue::Three),
+                Wind::East,
+                Wind::South,
+                Wind::South,
+            ),
+            &ScoreTai::singapore(),
         )
         .unwrap();
-        assert_eq!(found_hu.melds, melds);
+        let mut expected = melds;
+        expected.push(
+            Meld::new(
+                vec![
+                    Tile::Wan(TileValue::Three),
+                    Tile::Wan(TileValue::Four),
+                    Tile::Wan(TileValue::Five),
+                ],
+                None,
+                MeldType::Chi,
+            )
+            .unwrap(),
+        );
+        expected.push(
+            Meld::new(
+                vec![Tile::Wan(TileValue::Four), Tile::Wan(TileValue::Four)],
+                None,
+                MeldType::Eye,
+            )
+            .unwrap(),
+        );
+        assert_eq!(found_hu.melds, expected);
+        assert!(found_hu.scores().contains(&Score::FullFlushPingHu));
     }
 
-    // #[test]
+    #[test]
     fn test_search_hu_allchi() {
         let mut hand = Hand::new();
         let melds = vec![
             Meld::new(
-                vec![Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
-                Some(Tile::Wan(TileValue::One)),
+                vec![Tile::Suo(TileValue::Two), Tile::Suo(TileValue::Three)],
+                Some(Tile::Suo(TileValue::One)),
                 MeldType::Chi,
             )
             .unwrap(),
             Meld::new(
-                vec![Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
-                Some(Tile::Wan(TileValue::One)),
+                vec![Tile::Tong(TileValue::Two), Tile::Tong(TileValue::Three)],
+                Some(Tile::Tong(TileValue::One)),
                 MeldType::Chi,
             )
             .unwrap(),
@@ -483,30 +882,121 @@ mod tests {
             )
             .unwrap(),
         ];
+        hand.draw(&Tile::Suo(TileValue::Two));
+        hand.draw(&Tile::Suo(TileValue::Three));
+        hand.draw(&Tile::Tong(TileValue::Two));
+        hand.draw(&Tile::Tong(TileValue::Three));
         hand.draw(&Tile::Wan(TileValue::Two));
-        hand.draw(&Tile::Wan(TileValue::Two));
-        hand.draw(&Tile::Wan(TileValue::Two));
-        hand.draw(&Tile::Wan(TileValue::Three));
-        hand.draw(&Tile::Wan(TileValue::Three));
         hand.draw(&Tile::Wan(TileValue::Three));
         hand.draw(&Tile::Wan(TileValue::Four));
         hand.draw(&Tile::Wan(TileValue::Five));
         hand.draw(&Tile::Wan(TileValue::Four));
         hand.draw(&Tile::Wan(TileValue::Four));
-        hand.draw(&Tile::Wind(Wind::East));
+        hand.draw(&Tile::Flower(Flower::Red(FlowerValue::One)));
         for meld in melds.iter() {
             hand.meld(meld.to_owned()).unwrap();
         }
-        assert_eq!(
-            search_hu(
-                &hand,
-                Some(&Tile::Wan(TileValue::Three)),
-                Vec::new(),
-                &Wind::South,
-                &Wind::South,
-                &HashMap::new()
+
+        let found_hu = search_hu(
+            &hand,
+            &WinContext::discard(
+                Tile::Wan(TileValue::Three),
+                Wind::East,
+                Wind::South,
+                Wind::South,
             ),
-            Some(Hu::new(melds, vec![Score::AllChi], &HashMap::new()))
+            &ScoreTai::singapore(),
+        )
+        .unwrap();
+        let mut expected = melds;
+        expected.push(
+            Meld::new(
+                vec![
+                    Tile::Wan(TileValue::Three),
+                    Tile::Wan(TileValue::Four),
+                    Tile::Wan(TileValue::Five),
+                ],
+                None,
+                MeldType::Chi,
+            )
+            .unwrap(),
         );
+        expected.push(
+            Meld::new(
+                vec![Tile::Wan(TileValue::Four), Tile::Wan(TileValue::Four)],
+                None,
+                MeldType::Eye,
+            )
+            .unwrap(),
+        );
+        assert_eq!(
+            found_hu,
+            Hu::new(expected, vec![Score::AllChi], &ScoreTai::singapore())
+                .with_waits(vec![Wait::TwoSided])
+        );
+    }
+
+    #[test]
+    fn test_classify_wait() {
+        let chi = Meld::new(
+            vec![Tile::Wan(TileValue::Seven), Tile::Wan(TileValue::Eight)],
+            Some(Tile::Wan(TileValue::Nine)),
+            MeldType::Chi,
+        )
+        .unwrap();
+        assert_eq!(
+            classify_wait(&chi, &Tile::Wan(TileValue::Seven)),
+            Some(Wait::Edge)
+        );
+        assert_eq!(
+            classify_wait(&chi, &Tile::Wan(TileValue::Eight)),
+            Some(Wait::Closed)
+        );
+        assert_eq!(
+            classify_wait(&chi, &Tile::Wan(TileValue::Nine)),
+            Some(Wait::TwoSided)
+        );
+        assert_eq!(classify_wait(&chi, &Tile::Suo(TileValue::Nine)), None);
+        let pong = Meld::new(vec![Tile::Dragon(Dragon::Fa); 3], None, MeldType::Pong).unwrap();
+        assert_eq!(
+            classify_wait(&pong, &Tile::Dragon(Dragon::Fa)),
+            Some(Wait::DualPong)
+        );
+    }
+
+    #[test]
+    fn test_search_hu_edge_wait_not_pinghu() {
+        let win = |winning_tile: Tile, other: Tile| {
+            let mut hand = Hand::new();
+            for tile in [
+                Tile::Wan(TileValue::Two),
+                other,
+                Tile::Suo(TileValue::Four),
+                Tile::Suo(TileValue::Five),
+                Tile::Suo(TileValue::Six),
+                Tile::Suo(TileValue::Seven),
+                Tile::Suo(TileValue::Eight),
+                Tile::Suo(TileValue::Nine),
+                Tile::Tong(TileValue::Two),
+                Tile::Tong(TileValue::Three),
+                Tile::Tong(TileValue::Four),
+                Tile::Tong(TileValue::Eight),
+                Tile::Tong(TileValue::Eight),
+            ] {
+                hand.draw(&tile);
+            }
+            let ctx = WinContext::discard(winning_tile, Wind::East, Wind::South, Wind::South);
+            search_hu(&hand, &ctx, &ScoreTai::singapore()).unwrap()
+        };
+
+        // 12 waiting on 3
+        let edge = win(Tile::Wan(TileValue::Three), Tile::Wan(TileValue::One));
+        assert_eq!(edge.waits(), &vec![Wait::Edge]);
+        assert!(!edge.scores().contains(&Score::PingHu));
+
+        // 23 waiting on 1 or 4
+        let two_sided = win(Tile::Wan(TileValue::One), Tile::Wan(TileValue::Three));
+        assert_eq!(two_sided.waits(), &vec![Wait::TwoSided]);
+        assert!(two_sided.scores().contains(&Score::PingHu));
     }
 }
@@ -1,5 +1,20 @@
+pub mod american;
+pub mod claim;
+pub mod context;
+pub mod corpus;
 pub mod error;
 pub mod hand;
+pub mod history;
+pub mod hk;
+pub mod hu;
+pub mod mcr;
 pub mod meld;
+pub mod riichi;
+pub mod round;
+pub mod rules;
+pub mod sichuan;
+pub mod tai;
+pub mod taiwan;
+pub mod three_player;
 pub mod tile;
-pub mod hu;
+pub mod view;
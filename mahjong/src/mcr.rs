@@ -0,0 +1,1271 @@
+use crate::{
+    context::{Replacement, WinContext},
+    hand::{ConcealedTiles, Hand},
+    hu::{classify_wait, is_thirteen_wonders, search_melds, HandShape, Wait},
+    meld::{Group, Meld, MeldType},
+    rules::{RuleSet, Scoring},
+    tile::{Dragon, Suit, Tile, TileValue, Wind},
+};
+
+// Chinese Official (MCR) fan patterns, from 1 fan up to 88 fan
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fan {
+    // 1 fan
+    PureDoubleChow,
+    MixedDoubleChow,
+    ShortStraight,
+    TwoTerminalChows,
+    PungOfTerminalsOrHonours,
+    MeldedKong,
+    OneVoidedSuit,
+    NoHonours,
+    EdgeWait,
+    ClosedWait,
+    SingleWait,
+    SelfDrawn,
+    FlowerTiles,
+    // 2 fan
+    DragonPung,
+    PrevalentWind,
+    SeatWind,
+    ConcealedHand,
+    AllChows,
+    TileHog,
+    DoublePung,
+    TwoConcealedPungs,
+    ConcealedKong,
+    AllSimples,
+    // 4 fan
+    OutsideHand,
+    FullyConcealedHand,
+    TwoMeldedKongs,
+    LastTile,
+    // 6 fan
+    AllPungs,
+    HalfFlush,
+    MixedShiftedChows,
+    AllTypes,
+    MeldedHand,
+    TwoDragonPungs,
+    // 8 fan
+    MixedStraight,
+    ReversibleTiles,
+    MixedTripleChow,
+    MixedShiftedPungs,
+    ChickenHand,
+    LastTileDraw,
+    LastTileClaim,
+    OutWithReplacementTile,
+    RobbingTheKong,
+    TwoConcealedKongs,
+    // 12 fan
+    LesserHonoursAndKnittedTiles,
+    KnittedStraight,
+    UpperFour,
+    LowerFour,
+    BigThreeWinds,
+    // 16 fan
+    PureStraight,
+    ThreeSuitedTerminalChows,
+    PureShiftedChows,
+    AllFives,
+    TriplePung,
+    ThreeConcealedPungs,
+    // 24 fan
+    SevenPairs,
+    GreaterHonoursAndKnittedTiles,
+    AllEvenPungs,
+    FullFlush,
+    PureTripleChow,
+    PureShiftedPungs,
+    UpperTiles,
+    MiddleTiles,
+    LowerTiles,
+    // 32 fan
+    FourPureShiftedChows,
+    ThreeKongs,
+    AllTerminalsAndHonours,
+    // 48 fan
+    QuadrupleChow,
+    FourPureShiftedPungs,
+    // 64 fan
+    AllTerminals,
+    LittleFourWinds,
+    LittleThreeDragons,
+    AllHonours,
+    FourConcealedPungs,
+    PureTerminalChows,
+    // 88 fan
+    BigFourWinds,
+    BigThreeDragons,
+    AllGreen,
+    NineGates,
+    FourKongs,
+    SevenShiftedPairs,
+    ThirteenOrphans,
+}
+
+impl Fan {
+    pub fn value(&self) -> u8 {
+        match self {
+            Fan::PureDoubleChow
+            | Fan::MixedDoubleChow
+            | Fan::ShortStraight
+            | Fan::TwoTerminalChows
+            | Fan::PungOfTerminalsOrHonours
+            | Fan::MeldedKong
+            | Fan::OneVoidedSuit
+            | Fan::NoHonours
+            | Fan::EdgeWait
+            | Fan::ClosedWait
+            | Fan::SingleWait
+            | Fan::SelfDrawn
+            | Fan::FlowerTiles => 1,
+            Fan::DragonPung
+            | Fan::PrevalentWind
+            | Fan::SeatWind
+            | Fan::ConcealedHand
+            | Fan::AllChows
+            | Fan::TileHog
+            | Fan::DoublePung
+            | Fan::TwoConcealedPungs
+            | Fan::ConcealedKong
+            | Fan::AllSimples => 2,
+            Fan::OutsideHand | Fan::FullyConcealedHand | Fan::TwoMeldedKongs | Fan::LastTile => 4,
+            Fan::AllPungs
+            | Fan::HalfFlush
+            | Fan::MixedShiftedChows
+            | Fan::AllTypes
+            | Fan::MeldedHand
+            | Fan::TwoDragonPungs
+            | Fan::TwoConcealedKongs => 6,
+            Fan::MixedStraight
+            | Fan::ReversibleTiles
+            | Fan::MixedTripleChow
+            | Fan::MixedShiftedPungs
+            | Fan::ChickenHand
+            | Fan::LastTileDraw
+            | Fan::LastTileClaim
+            | Fan::OutWithReplacementTile
+            | Fan::RobbingTheKong => 8,
+            Fan::LesserHonoursAndKnittedTiles
+            | Fan::KnittedStraight
+            | Fan::UpperFour
+            | Fan::LowerFour
+            | Fan::BigThreeWinds => 12,
+            Fan::PureStraight
+            | Fan::ThreeSuitedTerminalChows
+            | Fan::PureShiftedChows
+            | Fan::AllFives
+            | Fan::TriplePung
+            | Fan::ThreeConcealedPungs => 16,
+            Fan::SevenPairs
+            | Fan::GreaterHonoursAndKnittedTiles
+            | Fan::AllEvenPungs
+            | Fan::FullFlush
+            | Fan::PureTripleChow
+            | Fan::PureShiftedPungs
+            | Fan::UpperTiles
+            | Fan::MiddleTiles
+            | Fan::LowerTiles => 24,
+            Fan::FourPureShiftedChows | Fan::ThreeKongs | Fan::AllTerminalsAndHonours => 32,
+            Fan::QuadrupleChow | Fan::FourPureShiftedPungs => 48,
+            Fan::AllTerminals
+            | Fan::LittleFourWinds
+            | Fan::LittleThreeDragons
+            | Fan::AllHonours
+            | Fan::FourConcealedPungs
+            | Fan::PureTerminalChows => 64,
+            Fan::BigFourWinds
+            | Fan::BigThreeDragons
+            | Fan::AllGreen
+            | Fan::NineGates
+            | Fan::FourKongs
+            | Fan::SevenShiftedPairs
+            | Fan::ThirteenOrphans => 88,
+        }
+    }
+
+    // Fans that are necessarily part of this one and so are not scored alongside it
+    pub fn implied(&self) -> &'static [Fan] {
+        match self {
+            Fan::BigFourWinds => &[
+                Fan::LittleFourWinds,
+                Fan::BigThreeWinds,
+                Fan::AllPungs,
+                Fan::PrevalentWind,
+                Fan::SeatWind,
+                Fan::PungOfTerminalsOrHonours,
+            ],
+            Fan::BigThreeDragons => &[
+                Fan::LittleThreeDragons,
+                Fan::TwoDragonPungs,
+                Fan::DragonPung,
+            ],
+            Fan::AllGreen => &[Fan::HalfFlush],
+            Fan::NineGates => &[
+                Fan::FullFlush,
+                Fan::ConcealedHand,
+                Fan::PungOfTerminalsOrHonours,
+                Fan::NoHonours,
+            ],
+            Fan::FourKongs => &[
+                Fan::ThreeKongs,
+                Fan::TwoConcealedKongs,
+                Fan::TwoMeldedKongs,
+                Fan::ConcealedKong,
+                Fan::MeldedKong,
+                Fan::AllPungs,
+                Fan::SingleWait,
+            ],
+            Fan::SevenShiftedPairs => &[
+                Fan::SevenPairs,
+                Fan::FullFlush,
+                Fan::ConcealedHand,
+                Fan::SingleWait,
+                Fan::NoHonours,
+            ],
+            Fan::ThirteenOrphans => &[
+                Fan::AllTerminalsAndHonours,
+                Fan::AllTypes,
+                Fan::ConcealedHand,
+                Fan::SingleWait,
+            ],
+            Fan::AllTerminals => &[
+                Fan::AllTerminalsAndHonours,
+                Fan::AllPungs,
+                Fan::OutsideHand,
+                Fan::TriplePung,
+                Fan::DoublePung,
+                Fan::PungOfTerminalsOrHonours,
+                Fan::NoHonours,
+            ],
+            Fan::LittleFourWinds => &[Fan::BigThreeWinds],
+            Fan::LittleThreeDragons => &[Fan::TwoDragonPungs, Fan::DragonPung],
+            Fan::AllHonours => &[
+                Fan::AllTerminalsAndHonours,
+                Fan::AllPungs,
+                Fan::OutsideHand,
+                Fan::PungOfTerminalsOrHonours,
+            ],
+            Fan::FourConcealedPungs => &[
+                Fan::ThreeConcealedPungs,
+                Fan::TwoConcealedPungs,
+                Fan::AllPungs,
+                Fan::ConcealedHand,
+            ],
+            Fan::PureTerminalChows => &[
+                Fan::SevenPairs,
+                Fan::FullFlush,
+                Fan::AllChows,
+                Fan::PureDoubleChow,
+                Fan::TwoTerminalChows,
+                Fan::NoHonours,
+            ],
+            Fan::QuadrupleChow => &[
+                Fan::PureTripleChow,
+                Fan::PureShiftedPungs,
+                Fan::PureDoubleChow,
+                Fan::TileHog,
+            ],
+            Fan::FourPureShiftedPungs => &[Fan::PureShiftedPungs, Fan::AllPungs],
+            Fan::FourPureShiftedChows => &[Fan::PureShiftedChows],
+            Fan::ThreeKongs => &[
+                Fan::TwoConcealedKongs,
+                Fan::TwoMeldedKongs,
+                Fan::ConcealedKong,
+                Fan::MeldedKong,
+            ],
+            Fan::AllTerminalsAndHonours => &[
+                Fan::AllPungs,
+                Fan::OutsideHand,
+                Fan::PungOfTerminalsOrHonours,
+            ],
+            Fan::SevenPairs => &[Fan::ConcealedHand, Fan::SingleWait],
+            Fan::GreaterHonoursAndKnittedTiles => &[
+                Fan::LesserHonoursAndKnittedTiles,
+                Fan::ConcealedHand,
+                Fan::AllTypes,
+                Fan::SingleWait,
+            ],
+            Fan::AllEvenPungs => &[Fan::AllPungs, Fan::AllSimples, Fan::NoHonours],
+            Fan::FullFlush => &[Fan::OneVoidedSuit, Fan::NoHonours],
+            Fan::PureTripleChow => &[Fan::PureDoubleChow],
+            Fan::UpperTiles => &[Fan::UpperFour, Fan::NoHonours],
+            Fan::MiddleTiles => &[Fan::AllSimples, Fan::NoHonours],
+            Fan::LowerTiles => &[Fan::LowerFour, Fan::NoHonours],
+            Fan::PureStraight => &[Fan::ShortStraight, Fan::TwoTerminalChows],
+            Fan::ThreeSuitedTerminalChows => &[
+                Fan::AllChows,
+                Fan::MixedDoubleChow,
+                Fan::TwoTerminalChows,
+                Fan::NoHonours,
+            ],
+            Fan::AllFives => &[Fan::AllSimples, Fan::NoHonours],
+            Fan::TriplePung => &[Fan::DoublePung],
+            Fan::ThreeConcealedPungs => &[Fan::TwoConcealedPungs],
+            Fan::LesserHonoursAndKnittedTiles => {
+                &[Fan::ConcealedHand, Fan::AllTypes, Fan::SingleWait]
+            }
+            Fan::UpperFour | Fan::LowerFour => &[Fan::NoHonours],
+            Fan::ReversibleTiles => &[Fan::OneVoidedSuit],
+            Fan::MixedTripleChow => &[Fan::MixedDoubleChow],
+            Fan::LastTileDraw | Fan::OutWithReplacementTile => &[Fan::SelfDrawn],
+            Fan::RobbingTheKong => &[Fan::LastTile],
+            Fan::TwoConcealedKongs => &[Fan::ConcealedKong, Fan::TwoConcealedPungs],
+            Fan::MeldedHand => &[Fan::SingleWait],
+            Fan::TwoDragonPungs => &[Fan::DragonPung],
+            Fan::FullyConcealedHand => &[Fan::SelfDrawn, Fan::ConcealedHand],
+            Fan::TwoMeldedKongs => &[Fan::MeldedKong],
+            Fan::AllChows | Fan::AllSimples => &[Fan::NoHonours],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McrHu {
+    melds: Vec<Meld>,
+    fans: Vec<Fan>,
+    fan: u32,
+}
+
+impl McrHu {
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn fans(&self) -> &Vec<Fan> {
+        &self.fans
+    }
+
+    pub fn fan(&self) -> u32 {
+        self.fan
+    }
+
+    // Flower tiles are paid out but do not count towards the minimum
+    pub fn fan_without_flowers(&self) -> u32 {
+        self.fan - self.fans.iter().filter(|f| **f == Fan::FlowerTiles).count() as u32
+    }
+}
+
+impl std::cmp::Ord for McrHu {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.fan
+            .cmp(&other.fan)
+            .then_with(|| other.melds.cmp(&self.melds))
+            .then_with(|| other.fans.cmp(&self.fans))
+    }
+}
+
+impl std::cmp::PartialOrd for McrHu {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChineseOfficial {
+    pub minimum: u32,
+    // Paid by every other player on top of the fan
+    pub base: u32,
+}
+
+impl Default for ChineseOfficial {
+    fn default() -> Self {
+        Self {
+            minimum: 8,
+            base: 8,
+        }
+    }
+}
+
+const NUMBER_SUITS: [Suit; 3] = [Suit::Wan, Suit::Suo, Suit::Tong];
+
+fn numbered(suit: Suit, number: u8) -> Option<Tile> {
+    let value = TileValue::from_number(number)?;
+    match suit {
+        Suit::Wan => Some(Tile::Wan(value)),
+        Suit::Suo => Some(Tile::Suo(value)),
+        Suit::Tong => Some(Tile::Tong(value)),
+        _ => None,
+    }
+}
+
+// 147, 258 and 369 each in a different suit, for every ordering of the suits
+fn knitted_straights() -> Vec<Vec<Tile>> {
+    let mut straights = Vec::new();
+    for first in NUMBER_SUITS {
+        for second in NUMBER_SUITS.into_iter().filter(|s| *s != first) {
+            let third = NUMBER_SUITS
+                .into_iter()
+                .find(|s| *s != first && *s != second)
+                .unwrap();
+            let mut tiles = Vec::new();
+            for (suit, start) in [(first, 1), (second, 2), (third, 3)] {
+                for number in [start, start + 3, start + 6] {
+                    tiles.push(numbered(suit, number).unwrap());
+                }
+            }
+            straights.push(tiles);
+        }
+    }
+    straights
+}
+
+fn is_seven_pairs(concealed: &ConcealedTiles) -> bool {
+    concealed.values().sum::<u8>() == 14 && concealed.values().all(|c| c % 2 == 0)
+}
+
+// Fourteen different tiles taken from a knitted straight and the honours
+fn is_honours_and_knitted(concealed: &ConcealedTiles) -> bool {
+    concealed.len() == 14
+        && concealed.values().all(|c| *c == 1)
+        && knitted_straights().iter().any(|straight| {
+            concealed
+                .keys()
+                .all(|t| t.is_honour() || straight.contains(t))
+        })
+}
+
+// The concealed tiles left once a full knitted straight is taken out
+fn without_knitted_straight(concealed: &ConcealedTiles) -> Vec<ConcealedTiles> {
+    knitted_straights()
+        .into_iter()
+        .filter_map(|straight| {
+            let mut rest = concealed.clone();
+            for tile in straight.iter() {
+                rest.remove_n(tile, 1).ok()?;
+            }
+            Some(rest)
+        })
+        .collect()
+}
+
+fn is_complete(concealed: &ConcealedTiles, hand: &Hand) -> bool {
+    let sets = HandShape::STANDARD.concealed_sets(hand);
+    !search_melds(concealed, sets).is_empty()
+        || (sets >= 3
+            && without_knitted_straight(concealed)
+                .iter()
+                .any(|rest| !search_melds(rest, sets - 3).is_empty()))
+        || (hand.melds().is_empty()
+            && (is_seven_pairs(concealed)
+                || is_thirteen_wonders(concealed)
+                || is_honours_and_knitted(concealed)))
+}
+
+// Tiles that would complete the concealed tiles
+fn waits(concealed: &ConcealedTiles, hand: &Hand) -> Vec<Tile> {
+    Tile::playable_tiles()
+        .into_iter()
+        .filter(|t| concealed.get(t).unwrap_or(&0) < &4)
+        .filter(|t| {
+            let mut next = concealed.clone();
+            next.add_n(t, 1);
+            is_complete(&next, hand)
+        })
+        .collect()
+}
+
+// 1112345678999 in one suit, waiting on any of the nine tiles
+fn is_pure_nine_gates(waiting: &ConcealedTiles) -> bool {
+    NUMBER_SUITS.into_iter().any(|suit| {
+        (1..=9).all(|n| {
+            let needed = match n {
+                1 | 9 => 3,
+                _ => 1,
+            };
+            waiting.get(&numbered(suit, n).unwrap()) == Some(&needed)
+        }) && waiting.values().sum::<u8>() == 13
+    })
+}
+
+// The fan formed by three chows, given as (suit, lowest number)
+fn three_chow_fan(chows: &[(Suit, u8)]) -> Option<Fan> {
+    let mut chows = chows.to_vec();
+    chows.sort_by_key(|c| c.1);
+    let numbers: Vec<u8> = chows.iter().map(|c| c.1).collect();
+    let step = numbers[1] - numbers[0];
+    let evenly_spaced = numbers[2] - numbers[1] == step;
+    let same_suit = chows.iter().all(|c| c.0 == chows[0].0);
+    let different_suits =
+        chows[0].0 != chows[1].0 && chows[1].0 != chows[2].0 && chows[0].0 != chows[2].0;
+    match (same_suit, different_suits, evenly_spaced, step) {
+        (true, _, true, 0) => Some(Fan::PureTripleChow),
+        (true, _, true, 3) if numbers[0] == 1 => Some(Fan::PureStraight),
+        (true, _, true, 1 | 2) => Some(Fan::PureShiftedChows),
+        (_, true, true, 3) if numbers[0] == 1 => Some(Fan::MixedStraight),
+        (_, true, true, 0) => Some(Fan::MixedTripleChow),
+        (_, true, true, 1) => Some(Fan::MixedShiftedChows),
+        _ => None,
+    }
+}
+
+fn two_chow_fan(first: &(Suit, u8), second: &(Suit, u8)) -> Option<Fan> {
+    let (low, high) = match first.1 <= second.1 {
+        true => (first, second),
+        false => (second, first),
+    };
+    match (low.0 == high.0, high.1 - low.1) {
+        (true, 0) => Some(Fan::PureDoubleChow),
+        (false, 0) => Some(Fan::MixedDoubleChow),
+        (true, 6) => Some(Fan::TwoTerminalChows),
+        (true, 3) => Some(Fan::ShortStraight),
+        _ => None,
+    }
+}
+
+impl ChineseOfficial {
+    fn new_hu(&self, mut melds: Vec<Meld>, mut fans: Vec<Fan>) -> McrHu {
+        let implied: Vec<Fan> = fans
+            .iter()
+            .flat_map(|f| f.implied().iter().copied())
+            .collect();
+        fans.retain(|f| !implied.contains(f));
+        if fans.iter().all(|f| *f == Fan::FlowerTiles) {
+            fans.push(Fan::ChickenHand);
+        }
+        melds.sort();
+        fans.sort();
+        let fan = fans.iter().map(|f| f.value() as u32).sum();
+        McrHu { melds, fans, fan }
+    }
+
+    // Every interpretation of the hand, including those below the minimum, best first
+    pub fn search_hus(&self, hand: &Hand, ctx: &WinContext) -> Vec<McrHu> {
+        let mut concealed = hand.concealed().normalized();
+        let winning_tile = ctx.winning_tile.normalized();
+        if ctx.claimed_tile().is_some() {
+            concealed.add_n(&winning_tile, 1);
+        }
+        let mut waiting = concealed.clone();
+        if waiting.remove_n(&winning_tile, 1).is_err() {
+            return Vec::new();
+        }
+        let only_wait = waits(&waiting, hand) == vec![winning_tile];
+        let closed = hand.melds().iter().all(|m| m.is_concealed_set());
+
+        let mut hand_fans = Vec::new();
+        match (ctx.self_drawn, closed) {
+            (true, true) => hand_fans.push(Fan::FullyConcealedHand),
+            (true, false) => hand_fans.push(Fan::SelfDrawn),
+            (false, true) => hand_fans.push(Fan::ConcealedHand),
+            (false, false) => (),
+        }
+        if ctx.last_tile {
+            match ctx.self_drawn {
+                true => hand_fans.push(Fan::LastTileDraw),
+                false => hand_fans.push(Fan::LastTileClaim),
+            }
+        }
+        if ctx.self_drawn && ctx.replacement == Some(Replacement::Kong) {
+            hand_fans.push(Fan::OutWithReplacementTile);
+        }
+        if ctx.robbing_kong {
+            hand_fans.push(Fan::RobbingTheKong);
+        }
+        if ctx.last_copy {
+            hand_fans.push(Fan::LastTile);
+        }
+        // Animals are not played in MCR
+        hand_fans.extend(hand.bonus().flowers().map(|_| Fan::FlowerTiles));
+
+        let mut hus = Vec::new();
+        if hand.melds().is_empty() {
+            let mut tiles = Vec::new();
+            for (tile, count) in concealed.iter() {
+                tiles.extend(vec![*tile; *count as usize]);
+            }
+            let mut special = Vec::new();
+            if is_thirteen_wonders(&concealed) {
+                special.push(vec![Fan::ThirteenOrphans]);
+            }
+            if is_seven_pairs(&concealed) {
+                let mut fans = vec![Fan::SevenPairs];
+                let mut pairs: Vec<&Tile> = concealed.keys().collect();
+                pairs.sort();
+                if pairs.len() == 7 && pairs.windows(2).all(|w| w[0].next().as_ref() == Some(w[1]))
+                {
+                    fans.push(Fan::SevenShiftedPairs);
+                }
+                special.push(fans);
+            }
+            if is_honours_and_knitted(&concealed) {
+                let mut fans = Vec::new();
+                match concealed.keys().filter(|t| t.is_honour()).count() {
+                    7 => fans.push(Fan::GreaterHonoursAndKnittedTiles),
+                    _ => fans.push(Fan::LesserHonoursAndKnittedTiles),
+                }
+                if concealed.keys().filter(|t| !t.is_honour()).count() == 9 {
+                    fans.push(Fan::KnittedStraight);
+                }
+                special.push(fans);
+            }
+            for mut fans in special {
+                fans.extend(hand_fans.iter().copied());
+                fans.extend(self.tile_fans(&tiles, &[]));
+                if only_wait {
+                    fans.push(Fan::SingleWait);
+                }
+                hus.push(self.new_hu(Vec::new(), fans));
+            }
+        }
+
+        let exposed: Vec<Group> = hand
+            .melds()
+            .iter()
+            .map(|m| Group::new(m, m.is_concealed_set()))
+            .collect();
+        let mut tiles: Vec<Tile> = hand
+            .melds()
+            .iter()
+            .flat_map(|m| m.all_tiles())
+            .map(|t| t.normalized())
+            .collect();
+        let kongs: Vec<Tile> = exposed
+            .iter()
+            .filter(|g| g.is_kan())
+            .map(|g| g.tile)
+            .collect();
+        for (tile, count) in concealed.iter() {
+            tiles.extend(vec![*tile; *count as usize]);
+        }
+        let tile_fans = self.tile_fans(&tiles, &kongs);
+
+        let sets = HandShape::STANDARD.concealed_sets(hand);
+        let mut decompositions: Vec<(Vec<Meld>, bool)> = search_melds(&concealed, sets)
+            .into_iter()
+            .map(|melds| (melds, false))
+            .collect();
+        if sets >= 3 {
+            for rest in without_knitted_straight(&concealed) {
+                decompositions.extend(
+                    search_melds(&rest, sets - 3)
+                        .into_iter()
+                        .map(|melds| (melds, true)),
+                );
+            }
+        }
+        for (poss_meld, knitted) in decompositions {
+            // The winning tile may complete any concealed group containing it, or the
+            // knitted straight
+            let mut win_groups: Vec<Option<usize>> = Vec::new();
+            if knitted {
+                win_groups.push(None);
+            }
+            for (i, meld) in poss_meld.iter().enumerate() {
+                if meld.tiles().contains(&winning_tile)
+                    && !win_groups
+                        .iter()
+                        .any(|j| j.is_some_and(|j| poss_meld[j] == *meld))
+                {
+                    win_groups.push(Some(i));
+                }
+            }
+            for win_group in win_groups {
+                let mut groups = exposed.clone();
+                for (i, meld) in poss_meld.iter().enumerate() {
+                    groups.push(Group::new(meld, ctx.self_drawn || Some(i) != win_group));
+                }
+                let mut fans = hand_fans.clone();
+                fans.extend(tile_fans.iter().copied());
+                fans.extend(self.group_fans(&groups, ctx, knitted));
+                if knitted {
+                    fans.push(Fan::KnittedStraight);
+                }
+                if hand.melds().is_empty() && is_pure_nine_gates(&waiting) {
+                    fans.push(Fan::NineGates);
+                }
+                if let Some(win_group) = win_group {
+                    let group = Group::new(&poss_meld[win_group], true);
+                    fans.extend(self.wait_fan(&poss_meld[win_group], &winning_tile, only_wait));
+                    if group.meld_type == MeldType::Eye
+                        && !ctx.self_drawn
+                        && hand.melds().iter().all(|m| !m.is_concealed_set())
+                        && hand.melds().len() == 4
+                    {
+                        fans.push(Fan::MeldedHand);
+                    }
+                }
+                let mut melds = hand.melds().clone();
+                melds.extend(poss_meld.iter().cloned());
+                hus.push(self.new_hu(melds, fans));
+            }
+        }
+
+        hus.sort_by(|a, b| b.cmp(a));
+        hus.dedup();
+        hus
+    }
+
+    // Best interpretation that reaches the fan minimum, flowers aside
+    pub fn search_hu(&self, hand: &Hand, ctx: &WinContext) -> Option<McrHu> {
+        self.search_hus(hand, ctx)
+            .into_iter()
+            .find(|hu| hu.fan_without_flowers() >= self.minimum)
+    }
+
+    // Fans that only depend on which tiles are in the hand, kongs counting four tiles
+    fn tile_fans(&self, tiles: &[Tile], kongs: &[Tile]) -> Vec<Fan> {
+        let mut fans = Vec::new();
+        let has_honours = tiles.iter().any(|t| t.is_honour());
+        let mut suits: Vec<Suit> = tiles
+            .iter()
+            .filter(|t| !t.is_honour())
+            .map(|t| t.suit())
+            .collect();
+        suits.sort();
+        suits.dedup();
+        let numbers: Vec<u8> = tiles.iter().filter_map(|t| t.number()).collect();
+
+        let green = [
+            Tile::Suo(TileValue::Two),
+            Tile::Suo(TileValue::Three),
+            Tile::Suo(TileValue::Four),
+            Tile::Suo(TileValue::Six),
+            Tile::Suo(TileValue::Eight),
+            Tile::Dragon(Dragon::Fa),
+        ];
+        if tiles.iter().all(|t| green.contains(t)) {
+            fans.push(Fan::AllGreen);
+        }
+        let reversible = [
+            Tile::Tong(TileValue::One),
+            Tile::Tong(TileValue::Two),
+            Tile::Tong(TileValue::Three),
+            Tile::Tong(TileValue::Four),
+            Tile::Tong(TileValue::Five),
+            Tile::Tong(TileValue::Eight),
+            Tile::Tong(TileValue::Nine),
+            Tile::Suo(TileValue::Two),
+            Tile::Suo(TileValue::Four),
+            Tile::Suo(TileValue::Five),
+            Tile::Suo(TileValue::Six),
+            Tile::Suo(TileValue::Eight),
+            Tile::Suo(TileValue::Nine),
+            Tile::Dragon(Dragon::Baiban),
+        ];
+        if tiles.iter().all(|t| reversible.contains(t)) {
+            fans.push(Fan::ReversibleTiles);
+        }
+
+        if tiles.iter().all(|t| t.is_terminal()) {
+            fans.push(Fan::AllTerminals);
+        } else if tiles.iter().all(|t| t.is_honour()) {
+            fans.push(Fan::AllHonours);
+        } else if tiles.iter().all(|t| t.is_terminal() || t.is_honour()) {
+            fans.push(Fan::AllTerminalsAndHonours);
+        }
+
+        match (suits.len(), has_honours) {
+            (1, false) => fans.push(Fan::FullFlush),
+            (1, true) => fans.push(Fan::HalfFlush),
+            (2, _) => fans.push(Fan::OneVoidedSuit),
+            _ => (),
+        }
+        if suits.len() == 3
+            && tiles.iter().any(|t| matches!(t, Tile::Wind(_)))
+            && tiles.iter().any(|t| matches!(t, Tile::Dragon(_)))
+        {
+            fans.push(Fan::AllTypes);
+        }
+
+        if !has_honours {
+            let all_within = |low: u8, high: u8| numbers.iter().all(|n| (low..=high).contains(n));
+            if all_within(7, 9) {
+                fans.push(Fan::UpperTiles);
+            } else if all_within(4, 6) {
+                fans.push(Fan::MiddleTiles);
+            } else if all_within(1, 3) {
+                fans.push(Fan::LowerTiles);
+            } else if all_within(6, 9) {
+                fans.push(Fan::UpperFour);
+            } else if all_within(1, 4) {
+                fans.push(Fan::LowerFour);
+            }
+            if tiles.iter().all(|t| !t.is_terminal()) {
+                fans.push(Fan::AllSimples);
+            }
+            fans.push(Fan::NoHonours);
+        }
+
+        let mut kinds = tiles.to_vec();
+        kinds.sort();
+        kinds.dedup();
+        for kind in kinds {
+            if tiles.iter().filter(|t| **t == kind).count() == 4 && !kongs.contains(&kind) {
+                fans.push(Fan::TileHog);
+            }
+        }
+        fans
+    }
+
+    // Fans that depend on how the hand splits into sets
+    fn group_fans(&self, groups: &[Group], ctx: &WinContext, knitted: bool) -> Vec<Fan> {
+        let mut fans = Vec::new();
+        let sets: Vec<&Group> = groups
+            .iter()
+            .filter(|g| g.meld_type != MeldType::Eye)
+            .collect();
+        let pungs: Vec<&Group> = sets.iter().filter(|g| g.is_pon()).copied().collect();
+        let chows: Vec<(Suit, u8)> = sets
+            .iter()
+            .filter(|g| g.meld_type == MeldType::Chi)
+            .map(|g| (g.tile.suit(), g.tile.number().unwrap()))
+            .collect();
+        let Some(eye) = groups.iter().find(|g| g.meld_type == MeldType::Eye) else {
+            return fans;
+        };
+
+        let dragon_pungs = pungs
+            .iter()
+            .filter(|g| matches!(g.tile, Tile::Dragon(_)))
+            .count();
+        match (dragon_pungs, eye.tile) {
+            (3, _) => fans.push(Fan::BigThreeDragons),
+            (2, Tile::Dragon(_)) => fans.push(Fan::LittleThreeDragons),
+            (2, _) => fans.push(Fan::TwoDragonPungs),
+            (1, _) => fans.push(Fan::DragonPung),
+            _ => (),
+        }
+        let wind_pungs = pungs
+            .iter()
+            .filter(|g| matches!(g.tile, Tile::Wind(_)))
+            .count();
+        match (wind_pungs, eye.tile) {
+            (4, _) => fans.push(Fan::BigFourWinds),
+            (3, Tile::Wind(_)) => fans.push(Fan::LittleFourWinds),
+            (3, _) => fans.push(Fan::BigThreeWinds),
+            _ => (),
+        }
+        for pung in pungs.iter() {
+            if pung.tile == Tile::Wind(ctx.prevailing_wind) {
+                fans.push(Fan::PrevalentWind);
+            }
+            if pung.tile == Tile::Wind(ctx.seat_wind) {
+                fans.push(Fan::SeatWind);
+            }
+            // Wind pungs already scored as seat, prevalent or three winds are not counted again
+            let scored_wind = match pung.tile {
+                Tile::Wind(w) => w == ctx.seat_wind || w == ctx.prevailing_wind || wind_pungs >= 3,
+                _ => false,
+            };
+            if pung.tile.is_terminal() || (matches!(pung.tile, Tile::Wind(_)) && !scored_wind) {
+                fans.push(Fan::PungOfTerminalsOrHonours);
+            }
+        }
+
+        let concealed_kongs = pungs
+            .iter()
+            .filter(|g| g.meld_type == MeldType::AnGang)
+            .count();
+        let kongs = pungs.iter().filter(|g| g.is_kan()).count();
+        match (kongs, concealed_kongs) {
+            (4, _) => fans.push(Fan::FourKongs),
+            (3, _) => fans.push(Fan::ThreeKongs),
+            (2, 2) => fans.push(Fan::TwoConcealedKongs),
+            (2, 1) => fans.extend([Fan::ConcealedKong, Fan::MeldedKong]),
+            (2, _) => fans.push(Fan::TwoMeldedKongs),
+            (1, 1) => fans.push(Fan::ConcealedKong),
+            (1, _) => fans.push(Fan::MeldedKong),
+            _ => (),
+        }
+        match pungs.iter().filter(|g| g.concealed).count() {
+            4 => fans.push(Fan::FourConcealedPungs),
+            3 => fans.push(Fan::ThreeConcealedPungs),
+            2 => fans.push(Fan::TwoConcealedPungs),
+            _ => (),
+        }
+        if pungs.len() == 4 {
+            fans.push(Fan::AllPungs);
+            if groups
+                .iter()
+                .all(|g| g.tile.number().is_some_and(|n| n % 2 == 0))
+            {
+                fans.push(Fan::AllEvenPungs);
+            }
+        }
+        if !knitted {
+            if groups
+                .iter()
+                .all(|g| g.tiles().iter().any(|t| t.number() == Some(5)))
+            {
+                fans.push(Fan::AllFives);
+            }
+            if groups.iter().all(|g| g.has_terminal_or_honour()) {
+                fans.push(Fan::OutsideHand);
+            }
+        }
+        if chows.len() == 4 && !eye.tile.is_honour() {
+            fans.push(Fan::AllChows);
+        }
+
+        // Pungs in the numbered suits
+        let numbered_pungs: Vec<(Suit, u8)> = pungs
+            .iter()
+            .filter_map(|g| Some((g.tile.suit(), g.tile.number()?)))
+            .collect();
+        for number in 1..=9 {
+            match numbered_pungs.iter().filter(|p| p.1 == number).count() {
+                3 => fans.push(Fan::TriplePung),
+                2 => fans.push(Fan::DoublePung),
+                _ => (),
+            }
+        }
+        let mut shifted = 0;
+        for suit in NUMBER_SUITS {
+            for start in 1..=9 {
+                let run = (start..=9)
+                    .take_while(|n| numbered_pungs.contains(&(suit, *n)))
+                    .count();
+                shifted = shifted.max(run);
+            }
+        }
+        match shifted {
+            4 => fans.push(Fan::FourPureShiftedPungs),
+            3 => fans.push(Fan::PureShiftedPungs),
+            _ => {
+                if numbered_pungs.iter().any(|(suit, n)| {
+                    NUMBER_SUITS.iter().any(|s2| {
+                        NUMBER_SUITS.iter().any(|s3| {
+                            s2 != suit
+                                && s3 != suit
+                                && s2 != s3
+                                && numbered_pungs.contains(&(*s2, n + 1))
+                                && numbered_pungs.contains(&(*s3, n + 2))
+                        })
+                    })
+                }) {
+                    fans.push(Fan::MixedShiftedPungs);
+                }
+            }
+        }
+
+        fans.extend(self.chow_fans(&chows, eye));
+        fans
+    }
+
+    // Each chow combines into at most one smaller fan once it is part of a larger one
+    fn chow_fans(&self, chows: &[(Suit, u8)], eye: &Group) -> Vec<Fan> {
+        let mut fans = Vec::new();
+        if chows.len() == 4 {
+            let mut sorted = chows.to_vec();
+            sorted.sort();
+            let same_suit = sorted.iter().all(|c| c.0 == sorted[0].0);
+            let step = sorted[1].1 - sorted[0].1;
+            let eye_five = |suit: Suit| Some(eye.tile) == numbered(suit, 5);
+            if same_suit && sorted.windows(2).all(|w| w[1].1 - w[0].1 == step) {
+                match step {
+                    0 => return vec![Fan::QuadrupleChow],
+                    1 | 2 => return vec![Fan::FourPureShiftedChows],
+                    _ => (),
+                }
+            }
+            let numbers: Vec<u8> = sorted.iter().map(|c| c.1).collect();
+            if same_suit && numbers == [1, 1, 7, 7] && eye_five(sorted[0].0) {
+                return vec![Fan::PureTerminalChows];
+            }
+            let terminal_suits: Vec<Suit> = NUMBER_SUITS
+                .into_iter()
+                .filter(|s| sorted.contains(&(*s, 1)) && sorted.contains(&(*s, 7)))
+                .collect();
+            if terminal_suits.len() == 2
+                && NUMBER_SUITS
+                    .into_iter()
+                    .any(|s| !terminal_suits.contains(&s) && eye_five(s))
+            {
+                return vec![Fan::ThreeSuitedTerminalChows];
+            }
+        }
+
+        let mut counted = vec![false; chows.len()];
+        let mut best: Option<(Fan, [usize; 3])> = None;
+        for i in 0..chows.len() {
+            for j in i + 1..chows.len() {
+                for k in j + 1..chows.len() {
+                    let Some(fan) = three_chow_fan(&[chows[i], chows[j], chows[k]]) else {
+                        continue;
+                    };
+                    if best.is_none_or(|(b, _)| fan.value() > b.value()) {
+                        best = Some((fan, [i, j, k]));
+                    }
+                }
+            }
+        }
+        if let Some((fan, used)) = best {
+            fans.push(fan);
+            for i in used {
+                counted[i] = true;
+            }
+        }
+        for i in 0..chows.len() {
+            for j in i + 1..chows.len() {
+                if counted[i] && counted[j] {
+                    continue;
+                }
+                if let Some(fan) = two_chow_fan(&chows[i], &chows[j]) {
+                    fans.push(fan);
+                    counted[i] = true;
+                    counted[j] = true;
+                }
+            }
+        }
+        fans
+    }
+
+    // Edge, closed and single waits only score when the hand could win on no other tile
+    fn wait_fan(&self, meld: &Meld, winning_tile: &Tile, only_wait: bool) -> Option<Fan> {
+        if !only_wait {
+            return None;
+        }
+        match classify_wait(meld, winning_tile)? {
+            Wait::Edge => Some(Fan::EdgeWait),
+            Wait::Closed => Some(Fan::ClosedWait),
+            Wait::SingleEye => Some(Fan::SingleWait),
+            Wait::TwoSided | Wait::DualPong => None,
+        }
+    }
+
+    // Every other player pays the base, and whoever pays for the win adds the fan
+    pub fn payments(&self, hu: &McrHu, ctx: &WinContext) -> Vec<(Wind, u32)> {
+        ctx.seats
+            .iter()
+            .copied()
+            .filter(|w| w != &ctx.seat_wind)
+            .map(|w| match ctx.self_drawn || ctx.discarder == Some(w) {
+                true => (w, self.base + hu.fan),
+                false => (w, self.base),
+            })
+            .collect()
+    }
+}
+
+impl RuleSet for ChineseOfficial {
+    type Pattern = Fan;
+
+    fn score_all(&self, hand: &Hand, ctx: &WinContext) -> Vec<Scoring<Fan>> {
+        self.search_hus(hand, ctx)
+            .into_iter()
+            .filter(|hu| hu.fan_without_flowers() >= self.minimum)
+            .map(|hu| Scoring {
+                melds: hu.melds,
+                patterns: hu.fans,
+                value: hu.fan,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        context::WinContext,
+        hand::Hand,
+        meld::{Meld, MeldType},
+        rules::RuleSet,
+        tile::{Flower, FlowerValue, Tile, TileValue, Wind},
+    };
+
+    use super::{ChineseOfficial, Fan};
+
+    fn draw_all(hand: &mut Hand, tiles: &[Tile]) {
+        for tile in tiles {
+            hand.draw(tile);
+        }
+    }
+
+    fn straight_hand() -> Hand {
+        let mut hand = Hand::new();
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Wan(TileValue::One),
+                Tile::Wan(TileValue::Two),
+                Tile::Wan(TileValue::Three),
+                Tile::Wan(TileValue::Four),
+                Tile::Wan(TileValue::Five),
+                Tile::Wan(TileValue::Six),
+                Tile::Wan(TileValue::Seven),
+                Tile::Wan(TileValue::Eight),
+                Tile::Wan(TileValue::Nine),
+                Tile::Tong(TileValue::Two),
+                Tile::Tong(TileValue::Three),
+                Tile::Tong(TileValue::Four),
+                Tile::Suo(TileValue::Five),
+            ],
+        );
+        hand
+    }
+
+    #[test]
+    fn test_pure_straight() {
+        let ctx = WinContext::discard(
+            Tile::Suo(TileValue::Five),
+            Wind::North,
+            Wind::South,
+            Wind::East,
+        );
+        let hu = ChineseOfficial::default()
+            .search_hu(&straight_hand(), &ctx)
+            .unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![
+                Fan::SingleWait,
+                Fan::ConcealedHand,
+                Fan::AllChows,
+                Fan::PureStraight
+            ]
+        );
+        assert_eq!(hu.fan(), 21);
+    }
+
+    #[test]
+    fn test_implied_fans_excluded() {
+        let mut hand = Hand::new();
+        let mut value = Some(TileValue::One);
+        while let Some(v) = value.filter(|v| v <= &TileValue::Seven) {
+            draw_all(&mut hand, &[Tile::Suo(v), Tile::Suo(v)]);
+            value = v.next();
+        }
+        let ctx = WinContext::self_drawn(Tile::Suo(TileValue::Seven), Wind::East, Wind::East);
+        let hu = ChineseOfficial::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![Fan::FullyConcealedHand, Fan::SevenShiftedPairs]
+        );
+        assert_eq!(hu.fan(), 92);
+
+        let mut hand = Hand::new();
+        for wind in Wind::ALL {
+            draw_all(&mut hand, &[Tile::Wind(wind); 3]);
+        }
+        draw_all(&mut hand, &[Tile::Wan(TileValue::Nine); 2]);
+        let ctx = WinContext::self_drawn(Tile::Wan(TileValue::Nine), Wind::South, Wind::East);
+        let hu = ChineseOfficial::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![
+                Fan::SingleWait,
+                Fan::FullyConcealedHand,
+                Fan::HalfFlush,
+                Fan::AllTerminalsAndHonours,
+                Fan::FourConcealedPungs,
+                Fan::BigFourWinds
+            ]
+        );
+        assert!(Fan::BigFourWinds
+            .implied()
+            .contains(&Fan::PungOfTerminalsOrHonours));
+    }
+
+    #[test]
+    fn test_flowers_do_not_reach_minimum() {
+        let mut hand = Hand::new();
+        draw_all(
+            &mut hand,
+            &[Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
+        );
+        hand.meld(
+            Meld::new(
+                vec![Tile::Wan(TileValue::Two), Tile::Wan(TileValue::Three)],
+                Some(Tile::Wan(TileValue::Four)),
+                MeldType::Chi,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Tong(TileValue::Five),
+                Tile::Tong(TileValue::Six),
+                Tile::Tong(TileValue::Seven),
+                Tile::Suo(TileValue::Three),
+                Tile::Suo(TileValue::Four),
+                Tile::Suo(TileValue::Five),
+                Tile::Suo(TileValue::Six),
+                Tile::Suo(TileValue::Seven),
+                Tile::Suo(TileValue::Eight),
+                Tile::Wan(TileValue::Eight),
+            ],
+        );
+        for value in [
+            FlowerValue::One,
+            FlowerValue::Two,
+            FlowerValue::Three,
+            FlowerValue::Four,
+        ] {
+            hand.draw(&Tile::Flower(Flower::Red(value)));
+        }
+        let ctx = WinContext::discard(
+            Tile::Wan(TileValue::Eight),
+            Wind::East,
+            Wind::South,
+            Wind::East,
+        );
+        let rules = ChineseOfficial::default();
+        let hus = rules.search_hus(&hand, &ctx);
+        assert_eq!(hus[0].fan(), 10);
+        assert_eq!(hus[0].fan_without_flowers(), 6);
+        assert_eq!(rules.search_hu(&hand, &ctx), None);
+        assert_eq!(rules.score(&hand, &ctx), None);
+    }
+
+    #[test]
+    fn test_two_concealed_kongs() {
+        let mut hand = Hand::new();
+        for tile in [Tile::Wan(TileValue::Two), Tile::Tong(TileValue::Five)] {
+            draw_all(&mut hand, &[tile; 4]);
+            hand.meld(Meld::new(vec![tile; 4], None, MeldType::AnGang).unwrap())
+                .unwrap();
+        }
+        draw_all(
+            &mut hand,
+            &[
+                Tile::Suo(TileValue::Three),
+                Tile::Suo(TileValue::Four),
+                Tile::Suo(TileValue::Five),
+                Tile::Suo(TileValue::Six),
+                Tile::Suo(TileValue::Seven),
+                Tile::Suo(TileValue::Eight),
+                Tile::Wan(TileValue::Nine),
+            ],
+        );
+        let ctx = WinContext::discard(
+            Tile::Wan(TileValue::Nine),
+            Wind::North,
+            Wind::South,
+            Wind::East,
+        );
+        let hu = ChineseOfficial::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![
+                Fan::ShortStraight,
+                Fan::NoHonours,
+                Fan::SingleWait,
+                Fan::ConcealedHand,
+                Fan::TwoConcealedKongs
+            ]
+        );
+        assert_eq!(hu.fan(), 11);
+    }
+
+    #[test]
+    fn test_payments() {
+        let ctx = WinContext::discard(
+            Tile::Suo(TileValue::Five),
+            Wind::North,
+            Wind::South,
+            Wind::East,
+        );
+        let rules = ChineseOfficial::default();
+        let hu = rules.search_hu(&straight_hand(), &ctx).unwrap();
+        assert_eq!(
+            rules.payments(&hu, &ctx),
+            vec![(Wind::East, 8), (Wind::West, 8), (Wind::North, 29)]
+        );
+    }
+}
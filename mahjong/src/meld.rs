@@ -1,14 +1,32 @@
 use crate::{
+    claim::RelativeSeat,
     error::MahjongError,
-    tile::{Suit, Tile},
+    tile::{Suit, Tile, Wind},
 };
 
+// Where the claimed tile of an exposed meld came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Provenance {
+    pub seat: Wind,
+    pub from: RelativeSeat,
+    // The number of discards made in the hand up to and including the claimed one
+    pub turn: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Upright,
+    // Turned to point at the player the tile was claimed from
+    Sideways,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Meld {
     tiles: Vec<Tile>,
     discarded_tile: Option<Tile>,
     meld_type: MeldType,
     suit: Suit,
+    provenance: Option<Provenance>,
 }
 
 impl Meld {
@@ -18,62 +36,70 @@ impl Meld {
         meld_type: MeldType,
     ) -> Result<Self, MahjongError> {
         tiles.sort();
-        let suit = tiles
-            .first()
-            .ok_or(MahjongError::InvalidMeldError(
+        if tiles.is_empty() {
+            return Err(MahjongError::WrongTileCountError(
                 meld_type,
-                tiles.clone(),
-                discarded_tile,
-            ))?
-            .suit();
-        if !tiles.first().unwrap().is_playable() {
+                meld_type.size(),
+                discarded_tile.iter().count(),
+            ));
+        }
+        if let Some(tile) = tiles
+            .iter()
+            .chain(&discarded_tile)
+            .find(|t| !t.is_playable())
+        {
+            return Err(MahjongError::TileNotPlayableError(tile.suit()));
+        }
+        let mut all_tiles: Vec<Tile> = tiles.iter().map(|t| t.normalized()).collect();
+        if let Some(discarded) = discarded_tile {
+            all_tiles.push(discarded.normalized())
+        }
+        all_tiles.sort();
+
+        // Jokers only stand in for tiles of a pong or kong, which still needs a natural tile
+        // and cannot be claimed from a discarded joker
+        let size = all_tiles.len();
+        all_tiles.retain(|t| *t != Tile::Joker);
+        if all_tiles.len() < size
+            && (!matches!(
+                meld_type,
+                MeldType::Pong | MeldType::Gang | MeldType::AnGang
+            ) || all_tiles.is_empty()
+                || discarded_tile == Some(Tile::Joker))
+        {
             return Err(MahjongError::InvalidMeldError(
                 meld_type,
                 tiles,
                 discarded_tile,
             ));
         }
-        let mut all_tiles = tiles.clone();
-        if let Some(discarded) = discarded_tile {
-            all_tiles.push(discarded)
+
+        if size != meld_type.size() {
+            return Err(MahjongError::WrongTileCountError(
+                meld_type,
+                meld_type.size(),
+                size,
+            ));
+        }
+        // Jokers sort last, so the suit comes from the natural tiles
+        let suit = all_tiles[0].suit();
+        let mut suits: Vec<Suit> = all_tiles.iter().map(|t| t.suit()).collect();
+        suits.dedup();
+        if suits.len() > 1 {
+            return Err(MahjongError::MixedSuitsError(meld_type, suits));
         }
-        all_tiles.sort();
 
         match meld_type {
             MeldType::Chi => {
-                if all_tiles.len() != 3
-                    || !all_tiles.windows(2).all(|w| match w[0].next() {
-                        Some(t) => t == w[1],
-                        None => false,
-                    })
-                {
-                    return Err(MahjongError::InvalidMeldError(
-                        meld_type,
-                        tiles,
-                        discarded_tile,
-                    ));
+                if let Some(honour) = all_tiles.iter().find(|t| t.is_honour()) {
+                    return Err(MahjongError::HonourChiError(*honour));
                 }
-            }
-            MeldType::Pong => {
-                if all_tiles.len() != 3 || !all_tiles.windows(2).all(|w| w[0] == w[1]) {
-                    return Err(MahjongError::InvalidMeldError(
-                        meld_type,
-                        tiles,
-                        discarded_tile,
-                    ));
+                if !all_tiles.windows(2).all(|w| w[0].next() == Some(w[1])) {
+                    return Err(MahjongError::NonConsecutiveChiError(all_tiles));
                 }
             }
-            MeldType::Gang | MeldType::AnGang => {
-                if all_tiles.len() != 4 || !all_tiles.windows(2).all(|w| w[0] == w[1]) {
-                    return Err(MahjongError::InvalidMeldError(
-                        meld_type,
-                        tiles,
-                        discarded_tile,
-                    ));
-                }
-            }
-            MeldType::Eye => {
-                if tiles.len() != 2 || tiles[0] != tiles[1] {
+            _ => {
+                if !all_tiles.windows(2).all(|w| w[0] == w[1]) {
                     return Err(MahjongError::InvalidMeldError(
                         meld_type,
                         tiles,
@@ -88,9 +114,45 @@ impl Meld {
             discarded_tile,
             meld_type,
             suit,
+            provenance: None,
         })
     }
 
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    pub fn provenance(&self) -> Option<&Provenance> {
+        self.provenance.as_ref()
+    }
+
+    // The seat whose discard was claimed
+    pub fn fed_by(&self) -> Option<Wind> {
+        self.provenance.map(|p| p.seat)
+    }
+
+    // The tiles as laid out on the table, the claimed tile turned sideways on the side of the
+    // player it came from: first from the left, second from across and last from the right
+    pub fn layout(&self) -> Vec<(Tile, Orientation)> {
+        let mut layout: Vec<(Tile, Orientation)> = self
+            .tiles
+            .iter()
+            .map(|t| (*t, Orientation::Upright))
+            .collect();
+        let (Some(tile), Some(provenance)) = (self.discarded_tile, self.provenance) else {
+            layout.extend(self.discarded_tile.map(|t| (t, Orientation::Upright)));
+            return layout;
+        };
+        let index = match provenance.from {
+            RelativeSeat::Left => 0,
+            RelativeSeat::Across => 1,
+            RelativeSeat::Right => layout.len(),
+        };
+        layout.insert(index, (tile, Orientation::Sideways));
+        layout
+    }
+
     pub fn tiles(&self) -> &Vec<Tile> {
         &self.tiles
     }
@@ -102,6 +164,23 @@ impl Meld {
     pub fn meld_type(&self) -> &MeldType {
         &self.meld_type
     }
+
+    pub fn discarded_tile(&self) -> Option<&Tile> {
+        self.discarded_tile.as_ref()
+    }
+
+    // The meld's own tiles together with the claimed discard, sorted
+    pub fn all_tiles(&self) -> Vec<Tile> {
+        let mut all_tiles = self.tiles.clone();
+        all_tiles.extend(self.discarded_tile);
+        all_tiles.sort();
+        all_tiles
+    }
+
+    // Sets formed without claiming a discard, including concealed kongs
+    pub fn is_concealed_set(&self) -> bool {
+        self.discarded_tile.is_none() && self.meld_type != MeldType::Eye
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -112,3 +191,156 @@ pub enum MeldType {
     AnGang,
     Eye,
 }
+
+impl MeldType {
+    // Tiles in the meld, the claimed discard included
+    pub fn size(&self) -> usize {
+        match self {
+            MeldType::Chi | MeldType::Pong => 3,
+            MeldType::Gang | MeldType::AnGang => 4,
+            MeldType::Eye => 2,
+        }
+    }
+}
+
+// A set of the hand with red fives merged, identified by its lowest tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Group {
+    pub(crate) meld_type: MeldType,
+    pub(crate) tile: Tile,
+    pub(crate) concealed: bool,
+}
+
+impl Group {
+    pub(crate) fn new(meld: &Meld, concealed: bool) -> Self {
+        Group {
+            meld_type: meld.meld_type,
+            tile: meld.all_tiles()[0].normalized(),
+            concealed,
+        }
+    }
+
+    pub(crate) fn is_pon(&self) -> bool {
+        matches!(
+            self.meld_type,
+            MeldType::Pong | MeldType::Gang | MeldType::AnGang
+        )
+    }
+
+    pub(crate) fn is_kan(&self) -> bool {
+        matches!(self.meld_type, MeldType::Gang | MeldType::AnGang)
+    }
+
+    pub(crate) fn tiles(&self) -> Vec<Tile> {
+        match self.meld_type {
+            MeldType::Chi => {
+                let second = self.tile.next().unwrap();
+                vec![self.tile, second, second.next().unwrap()]
+            }
+            MeldType::Eye => vec![self.tile; 2],
+            _ => vec![self.tile; 3],
+        }
+    }
+
+    pub(crate) fn has_terminal_or_honour(&self) -> bool {
+        self.tiles()
+            .iter()
+            .any(|t| t.is_terminal() || t.is_honour())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_matches};
+
+    use crate::{
+        claim::RelativeSeat,
+        error::MahjongError,
+        tile::{Dragon, Suit, Tile, TileValue, Wind},
+    };
+
+    use super::{Meld, MeldType, Orientation, Provenance};
+
+    #[test]
+    fn test_layout() {
+        let tile = Tile::Tong(TileValue::Seven);
+        let pong = Meld::new(vec![tile; 2], Some(tile), MeldType::Pong).unwrap();
+        assert!(pong
+            .layout()
+            .iter()
+            .all(|(_, o)| *o == Orientation::Upright));
+
+        let provenance = Provenance {
+            seat: Wind::North,
+            from: RelativeSeat::Across,
+            turn: 3,
+        };
+        let layout = pong.with_provenance(provenance).layout();
+        assert_eq!(
+            layout.iter().map(|(_, o)| *o).collect::<Vec<_>>(),
+            vec![
+                Orientation::Upright,
+                Orientation::Sideways,
+                Orientation::Upright
+            ]
+        );
+
+        let gang = Meld::new(vec![tile; 3], Some(tile), MeldType::Gang)
+            .unwrap()
+            .with_provenance(Provenance {
+                from: RelativeSeat::Right,
+                ..provenance
+            });
+        assert_eq!(gang.layout()[3], (tile, Orientation::Sideways));
+        assert_eq!(gang.fed_by(), Some(Wind::North));
+    }
+
+    #[test]
+    fn test_meld_errors() {
+        let (one, two, four) = (TileValue::One, TileValue::Two, TileValue::Four);
+        assert_matches!(
+            Meld::new(vec![Tile::Wan(one); 2], None, MeldType::Pong),
+            Err(MahjongError::WrongTileCountError(MeldType::Pong, 3, 2))
+        );
+        assert_matches!(
+            Meld::new(vec![Tile::Wan(one), Tile::Suo(two)], Some(Tile::Wan(four)), MeldType::Chi),
+            Err(MahjongError::MixedSuitsError(MeldType::Chi, suits)) if suits == vec![Suit::Wan, Suit::Suo]
+        );
+        assert_matches!(
+            Meld::new(
+                vec![Tile::Wan(one), Tile::Wan(two)],
+                Some(Tile::Wan(four)),
+                MeldType::Chi
+            ),
+            Err(MahjongError::NonConsecutiveChiError(_))
+        );
+        assert_matches!(
+            Meld::new(vec![Tile::Dragon(Dragon::Zhong); 3], None, MeldType::Chi),
+            Err(MahjongError::HonourChiError(Tile::Dragon(Dragon::Zhong)))
+        );
+    }
+
+    #[test]
+    fn test_joker_melds() {
+        let one = Tile::Wan(TileValue::One);
+        let pong = Meld::new(vec![Tile::Joker; 2], Some(one), MeldType::Pong).unwrap();
+        assert_eq!(pong.suit(), &Suit::Wan);
+        let gang = Meld::new(vec![Tile::Joker; 3], Some(one), MeldType::Gang).unwrap();
+        assert_eq!(gang.suit(), &Suit::Wan);
+        let angang = Meld::new(
+            vec![Tile::Joker, Tile::Joker, Tile::Joker, one],
+            None,
+            MeldType::AnGang,
+        )
+        .unwrap();
+        assert_eq!(angang.suit(), &Suit::Wan);
+
+        assert_err!(Meld::new(vec![Tile::Joker; 3], None, MeldType::Pong));
+        assert_err!(Meld::new(vec![one; 2], Some(Tile::Joker), MeldType::Pong));
+        assert_err!(Meld::new(
+            vec![Tile::Joker, Tile::Wan(TileValue::Two)],
+            Some(one),
+            MeldType::Chi
+        ));
+    }
+}
@@ -0,0 +1,1064 @@
+use crate::{
+    context::{Replacement, WinContext},
+    error::MahjongError,
+    hand::{ConcealedTiles, Hand},
+    hu::{classify_wait, is_nine_gates, is_thirteen_wonders, search_melds, HandShape, Wait},
+    meld::{Group, Meld, MeldType},
+    tile::{Dragon, Suit, Tile, TileValue, Wind},
+};
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Yaku {
+    Riichi,
+    DoubleRiichi,
+    Ippatsu,
+    MenzenTsumo,
+    Pinfu,
+    Tanyao,
+    Iipeikou,
+    Haku,
+    Hatsu,
+    Chun,
+    SeatWind,
+    RoundWind,
+    Haitei,
+    Houtei,
+    Rinshan,
+    Chankan,
+    Sanshoku,
+    Ittsu,
+    Chanta,
+    Toitoi,
+    Sanankou,
+    SanshokuDoukou,
+    Sankantsu,
+    Chiitoitsu,
+    Honroutou,
+    Shousangen,
+    Honitsu,
+    Junchan,
+    Ryanpeikou,
+    Chinitsu,
+    KokushiMusou,
+    Suuankou,
+    Daisangen,
+    Shousuushii,
+    Daisuushii,
+    Tsuuiisou,
+    Chinroutou,
+    Ryuuiisou,
+    ChuurenPoutou,
+    Suukantsu,
+}
+
+impl Yaku {
+    pub fn is_yakuman(&self) -> bool {
+        *self >= Yaku::KokushiMusou
+    }
+
+    // Han for a closed or open hand, 0 if the yaku requires a closed hand
+    pub fn han(&self, closed: bool) -> u8 {
+        match self {
+            Yaku::Riichi
+            | Yaku::Ippatsu
+            | Yaku::MenzenTsumo
+            | Yaku::Pinfu
+            | Yaku::Iipeikou
+            | Yaku::DoubleRiichi
+            | Yaku::Chiitoitsu
+            | Yaku::Ryanpeikou
+            | Yaku::KokushiMusou
+            | Yaku::Suuankou
+            | Yaku::ChuurenPoutou
+                if !closed =>
+            {
+                0
+            }
+            Yaku::DoubleRiichi | Yaku::Chiitoitsu => 2,
+            Yaku::Sanshoku | Yaku::Ittsu | Yaku::Chanta => 1 + closed as u8,
+            Yaku::Toitoi
+            | Yaku::Sanankou
+            | Yaku::SanshokuDoukou
+            | Yaku::Sankantsu
+            | Yaku::Honroutou
+            | Yaku::Shousangen => 2,
+            Yaku::Honitsu | Yaku::Junchan => 2 + closed as u8,
+            Yaku::Ryanpeikou => 3,
+            Yaku::Chinitsu => 5 + closed as u8,
+            y if y.is_yakuman() => 13,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RiichiDeclaration {
+    // Index of the declaring discard in the player's discards
+    pub turn: u32,
+    pub double: bool,
+    pub ippatsu: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct RiichiPlayer {
+    hand: Hand,
+    discards: Vec<Tile>,
+    riichi: Option<RiichiDeclaration>,
+    temporary_furiten: bool,
+    riichi_furiten: bool,
+}
+
+impl RiichiPlayer {
+    pub fn new(hand: Hand) -> Self {
+        Self {
+            hand,
+            ..Default::default()
+        }
+    }
+
+    pub fn draw(&mut self, tile: &Tile) {
+        self.hand.draw(tile);
+        self.temporary_furiten = false;
+    }
+
+    pub fn discard(&mut self, tile: &Tile) -> Result<(), MahjongError> {
+        self.hand.discard(tile)?;
+        self.discards.push(*tile);
+        // Ippatsu only lasts until the next discard after declaring
+        if let Some(riichi) = self.riichi.as_mut() {
+            if riichi.turn as usize + 1 < self.discards.len() {
+                riichi.ippatsu = false;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn declare_riichi(&mut self, tile: &Tile, double: bool) -> Result<(), MahjongError> {
+        if self.riichi.is_some() {
+            return Err(MahjongError::RiichiNotAllowedError(
+                "riichi has already been declared".to_owned(),
+            ));
+        }
+        if !self.hand.melds().iter().all(|m| m.is_concealed_set()) {
+            return Err(MahjongError::RiichiNotAllowedError(
+                "hand is not closed".to_owned(),
+            ));
+        }
+        let mut concealed = self.hand.concealed().clone();
+        concealed.remove_n(tile, 1)?;
+        if waits(&concealed, &self.hand).is_empty() {
+            return Err(MahjongError::RiichiNotAllowedError(format!(
+                "hand is not tenpai after discarding {:?}",
+                tile
+            )));
+        }
+        self.discard(tile)?;
+        self.riichi = Some(RiichiDeclaration {
+            turn: self.discards.len() as u32 - 1,
+            double,
+            ippatsu: true,
+        });
+        Ok(())
+    }
+
+    // Any call on the table interrupts ippatsu
+    pub fn break_ippatsu(&mut self) {
+        if let Some(riichi) = self.riichi.as_mut() {
+            riichi.ippatsu = false;
+        }
+    }
+
+    // Records that a winning discard was let go
+    pub fn pass_ron(&mut self, tile: &Tile) {
+        if self.waits().contains(&tile.normalized()) {
+            self.temporary_furiten = true;
+            if self.riichi.is_some() {
+                self.riichi_furiten = true;
+            }
+        }
+    }
+
+    pub fn waits(&self) -> Vec<Tile> {
+        waits(self.hand.concealed(), &self.hand)
+    }
+
+    pub fn is_furiten(&self) -> bool {
+        self.temporary_furiten
+            || self.riichi_furiten
+            || self
+                .waits()
+                .iter()
+                .any(|w| self.discards.iter().any(|d| d.normalized() == *w))
+    }
+
+    pub fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    pub fn discards(&self) -> &Vec<Tile> {
+        &self.discards
+    }
+
+    pub fn riichi(&self) -> Option<&RiichiDeclaration> {
+        self.riichi.as_ref()
+    }
+}
+
+fn is_complete(concealed: &ConcealedTiles, hand: &Hand) -> bool {
+    !search_melds(concealed, HandShape::STANDARD.concealed_sets(hand)).is_empty()
+        || (hand.melds().is_empty()
+            && (is_seven_pairs(concealed) || is_thirteen_wonders(concealed)))
+}
+
+fn is_seven_pairs(concealed: &ConcealedTiles) -> bool {
+    concealed.len() == 7 && concealed.values().all(|c| *c == 2)
+}
+
+// Tiles that would complete the concealed tiles
+fn waits(concealed: &ConcealedTiles, hand: &Hand) -> Vec<Tile> {
+    let concealed = concealed.normalized();
+    Tile::playable_tiles()
+        .into_iter()
+        .filter(|t| concealed.get(t).unwrap_or(&0) < &4)
+        .filter(|t| {
+            let mut next = concealed.clone();
+            next.add_n(t, 1);
+            is_complete(&next, hand)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadWall {
+    tiles: Vec<Tile>,
+    kans: usize,
+}
+
+impl DeadWall {
+    // [Replacement tiles x4, Dora indicators x5, Ura-dora indicators x5]
+    pub fn new(tiles: Vec<Tile>) -> Result<Self, MahjongError> {
+        if tiles.len() != 14 {
+            return Err(MahjongError::InvalidDeadWallError(tiles.len()));
+        }
+        Ok(Self { tiles, kans: 0 })
+    }
+
+    pub fn dora_indicators(&self) -> &[Tile] {
+        &self.tiles[4..5 + self.kans]
+    }
+
+    pub fn ura_dora_indicators(&self) -> &[Tile] {
+        &self.tiles[9..10 + self.kans]
+    }
+
+    // Draws a replacement tile after a kan, revealing the next dora indicator
+    pub fn draw_replacement(&mut self) -> Option<Tile> {
+        let tile = self.tiles[..4].get(self.kans).copied()?;
+        self.kans += 1;
+        Some(tile)
+    }
+}
+
+pub fn dora_from_indicator(indicator: &Tile) -> Tile {
+    match indicator.normalized() {
+        Tile::Wan(v) => Tile::Wan(v.next().unwrap_or(TileValue::One)),
+        Tile::Suo(v) => Tile::Suo(v.next().unwrap_or(TileValue::One)),
+        Tile::Tong(v) => Tile::Tong(v.next().unwrap_or(TileValue::One)),
+        Tile::Wind(w) => Tile::Wind(match w {
+            Wind::East => Wind::South,
+            Wind::South => Wind::West,
+            Wind::West => Wind::North,
+            Wind::North => Wind::East,
+        }),
+        Tile::Dragon(d) => Tile::Dragon(match d {
+            Dragon::Baiban => Dragon::Fa,
+            Dragon::Fa => Dragon::Zhong,
+            Dragon::Zhong => Dragon::Baiban,
+        }),
+        tile => tile,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RiichiHu {
+    melds: Vec<Meld>,
+    yaku: Vec<Yaku>,
+    han: u8,
+    fu: u8,
+    dora: u8,
+    ura_dora: u8,
+    aka_dora: u8,
+}
+
+impl RiichiHu {
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn yaku(&self) -> &Vec<Yaku> {
+        &self.yaku
+    }
+
+    // Total han including dora
+    pub fn han(&self) -> u8 {
+        self.han
+    }
+
+    pub fn fu(&self) -> u8 {
+        self.fu
+    }
+
+    pub fn dora(&self) -> u8 {
+        self.dora
+    }
+
+    pub fn ura_dora(&self) -> u8 {
+        self.ura_dora
+    }
+
+    pub fn aka_dora(&self) -> u8 {
+        self.aka_dora
+    }
+
+    pub fn is_yakuman(&self) -> bool {
+        self.yaku.iter().any(|y| y.is_yakuman())
+    }
+
+    pub fn basic_points(&self) -> u32 {
+        if self.is_yakuman() {
+            return 8000 * self.yaku.len() as u32;
+        }
+        match self.han {
+            13.. => 8000,
+            11..=12 => 6000,
+            8..=10 => 4000,
+            6..=7 => 3000,
+            5 => 2000,
+            han => (self.fu as u32 * 2_u32.pow(2 + han as u32)).min(2000),
+        }
+    }
+}
+
+impl std::cmp::Ord for RiichiHu {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.basic_points()
+            .cmp(&other.basic_points())
+            .then_with(|| self.han.cmp(&other.han))
+            .then_with(|| self.fu.cmp(&other.fu))
+            .then_with(|| other.melds.cmp(&self.melds))
+            .then_with(|| other.yaku.cmp(&self.yaku))
+    }
+}
+
+impl std::cmp::PartialOrd for RiichiHu {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Riichi {
+    // Kuitan, tanyao on an open hand
+    pub open_tanyao: bool,
+}
+
+impl Default for Riichi {
+    fn default() -> Self {
+        Self { open_tanyao: true }
+    }
+}
+
+fn round_up(points: u32, to: u32) -> u32 {
+    points.div_ceil(to) * to
+}
+
+impl Riichi {
+    // Points per honba counter, one counter for each hand the dealer has won or kept the deal
+    pub const HONBA: u32 = 100;
+
+    // Every interpretation with at least one yaku, best first. A furiten player cannot ron.
+    pub fn search_hus(
+        &self,
+        player: &RiichiPlayer,
+        ctx: &WinContext,
+        dead_wall: &DeadWall,
+    ) -> Vec<RiichiHu> {
+        if !ctx.self_drawn && player.is_furiten() {
+            return Vec::new();
+        }
+        let hand = player.hand();
+        let mut raw = hand.concealed().clone();
+        if let Some(tile) = ctx.claimed_tile() {
+            raw.add_n(tile, 1);
+        }
+        let concealed = raw.normalized();
+        let winning_tile = ctx.winning_tile.normalized();
+        let closed = hand.melds().iter().all(|m| m.is_concealed_set());
+
+        // Dora do not count as yaku, so they are added once a yaku is found
+        let all_tiles: Vec<Tile> = raw
+            .iter()
+            .flat_map(|(t, c)| vec![*t; *c as usize])
+            .chain(hand.melds().iter().flat_map(|m| m.all_tiles()))
+            .collect();
+        let count_dora = |indicators: &[Tile]| -> u8 {
+            indicators
+                .iter()
+                .map(|i| {
+                    let dora = dora_from_indicator(i);
+                    all_tiles.iter().filter(|t| t.normalized() == dora).count() as u8
+                })
+                .sum()
+        };
+        let dora = count_dora(dead_wall.dora_indicators());
+        let ura_dora = match player.riichi() {
+            Some(_) => count_dora(dead_wall.ura_dora_indicators()),
+            None => 0,
+        };
+        let aka_dora = all_tiles.iter().filter(|t| t.is_red()).count() as u8;
+
+        let mut situational = Vec::new();
+        if let Some(riichi) = player.riichi() {
+            match riichi.double {
+                true => situational.push(Yaku::DoubleRiichi),
+                false => situational.push(Yaku::Riichi),
+            }
+            if riichi.ippatsu {
+                situational.push(Yaku::Ippatsu);
+            }
+        }
+        if ctx.self_drawn && closed {
+            situational.push(Yaku::MenzenTsumo);
+        }
+        if ctx.last_tile {
+            match ctx.self_drawn {
+                true => situational.push(Yaku::Haitei),
+                false => situational.push(Yaku::Houtei),
+            }
+        }
+        if ctx.self_drawn && ctx.replacement == Some(Replacement::Kong) {
+            situational.push(Yaku::Rinshan);
+        }
+        if ctx.robbing_kong {
+            situational.push(Yaku::Chankan);
+        }
+
+        let new_hu = |melds: Vec<Meld>, mut yaku: Vec<Yaku>, fu: u8| -> Option<RiichiHu> {
+            if yaku.iter().any(|y| y.is_yakuman()) {
+                yaku.retain(|y| y.is_yakuman());
+            }
+            yaku.retain(|y| y.han(closed) > 0);
+            if yaku.is_empty() {
+                return None;
+            }
+            yaku.sort();
+            let mut han: u8 = yaku.iter().map(|y| y.han(closed)).sum();
+            if !yaku[0].is_yakuman() {
+                han += dora + ura_dora + aka_dora;
+            }
+            let mut melds = melds;
+            melds.sort();
+            Some(RiichiHu {
+                melds,
+                yaku,
+                han,
+                fu,
+                dora,
+                ura_dora,
+                aka_dora,
+            })
+        };
+
+        let mut hus = Vec::new();
+        if hand.melds().is_empty() && is_thirteen_wonders(&concealed) {
+            let mut yaku = situational.clone();
+            yaku.push(Yaku::KokushiMusou);
+            hus.extend(new_hu(Vec::new(), yaku, 30));
+        }
+        if hand.melds().is_empty() && is_seven_pairs(&concealed) {
+            let melds: Vec<Meld> = concealed
+                .keys()
+                .map(|t| Meld::new(vec![*t; 2], None, MeldType::Eye).unwrap())
+                .collect();
+            let groups: Vec<Group> = melds.iter().map(|m| Group::new(m, true)).collect();
+            let mut yaku = situational.clone();
+            yaku.push(Yaku::Chiitoitsu);
+            yaku.extend(self.tile_yaku(&groups, closed));
+            hus.extend(new_hu(melds, yaku, 25));
+        }
+
+        let exposed: Vec<Group> = hand
+            .melds()
+            .iter()
+            .map(|m| Group::new(m, m.is_concealed_set()))
+            .collect();
+        for poss_meld in search_melds(&concealed, HandShape::STANDARD.concealed_sets(hand)) {
+            // The winning tile may complete any concealed group containing it
+            let mut win_groups: Vec<usize> = Vec::new();
+            for (i, meld) in poss_meld.iter().enumerate() {
+                if meld.tiles().contains(&winning_tile)
+                    && !win_groups.iter().any(|j| poss_meld[*j] == *meld)
+                {
+                    win_groups.push(i);
+                }
+            }
+            for win_group in win_groups {
+                let mut groups = exposed.clone();
+                for (i, meld) in poss_meld.iter().enumerate() {
+                    groups.push(Group::new(meld, ctx.self_drawn || i != win_group));
+                }
+                let wait = classify_wait(&poss_meld[win_group], &winning_tile).unwrap();
+                let mut yaku = situational.clone();
+                yaku.extend(self.tile_yaku(&groups, closed));
+                yaku.extend(self.group_yaku(&groups, ctx, closed, wait));
+                if hand.melds().is_empty() && is_nine_gates(&concealed) {
+                    yaku.push(Yaku::ChuurenPoutou);
+                }
+                let fu = self.fu(&groups, ctx, closed, wait, yaku.contains(&Yaku::Pinfu));
+                let mut melds = hand.melds().clone();
+                melds.extend(poss_meld.iter().cloned());
+                hus.extend(new_hu(melds, yaku, fu));
+            }
+        }
+
+        hus.sort_by(|a, b| b.cmp(a));
+        hus.dedup();
+        hus
+    }
+
+    pub fn search_hu(
+        &self,
+        player: &RiichiPlayer,
+        ctx: &WinContext,
+        dead_wall: &DeadWall,
+    ) -> Option<RiichiHu> {
+        self.search_hus(player, ctx, dead_wall).into_iter().next()
+    }
+
+    // Yaku that only depend on which tiles are in the hand
+    fn tile_yaku(&self, groups: &[Group], closed: bool) -> Vec<Yaku> {
+        let mut yaku = Vec::new();
+        let tiles: Vec<Tile> = groups.iter().flat_map(|g| g.tiles()).collect();
+        if tiles.iter().all(|t| !t.is_terminal() && !t.is_honour()) && (closed || self.open_tanyao)
+        {
+            yaku.push(Yaku::Tanyao);
+        }
+
+        let mut suits: Vec<Suit> = tiles
+            .iter()
+            .map(|t| t.suit())
+            .filter(|s| matches!(s, Suit::Wan | Suit::Suo | Suit::Tong))
+            .collect();
+        suits.sort();
+        suits.dedup();
+        let has_honours = tiles.iter().any(|t| t.is_honour());
+        match (suits.len(), has_honours) {
+            (0, _) => yaku.push(Yaku::Tsuuiisou),
+            (1, true) => yaku.push(Yaku::Honitsu),
+            (1, false) => yaku.push(Yaku::Chinitsu),
+            _ => (),
+        }
+
+        if tiles.iter().all(|t| t.is_terminal()) {
+            yaku.push(Yaku::Chinroutou);
+        } else if !suits.is_empty() && tiles.iter().all(|t| t.is_terminal() || t.is_honour()) {
+            yaku.push(Yaku::Honroutou);
+        }
+
+        let green = |t: &Tile| {
+            matches!(
+                t,
+                Tile::Suo(
+                    TileValue::Two
+                        | TileValue::Three
+                        | TileValue::Four
+                        | TileValue::Six
+                        | TileValue::Eight
+                ) | Tile::Dragon(Dragon::Fa)
+            )
+        };
+        if tiles.iter().all(green) {
+            yaku.push(Yaku::Ryuuiisou);
+        }
+        yaku
+    }
+
+    // Yaku that depend on how the hand splits into sets
+    fn group_yaku(
+        &self,
+        groups: &[Group],
+        ctx: &WinContext,
+        closed: bool,
+        wait: Wait,
+    ) -> Vec<Yaku> {
+        let mut yaku = Vec::new();
+        let sets: Vec<&Group> = groups
+            .iter()
+            .filter(|g| g.meld_type != MeldType::Eye)
+            .collect();
+        let chis: Vec<&Group> = sets
+            .iter()
+            .filter(|g| g.meld_type == MeldType::Chi)
+            .copied()
+            .collect();
+        let pons: Vec<&Group> = sets.iter().filter(|g| g.is_pon()).copied().collect();
+        let Some(eye) = groups.iter().find(|g| g.meld_type == MeldType::Eye) else {
+            return yaku;
+        };
+        let is_yakuhai = |t: &Tile| {
+            matches!(t, Tile::Dragon(_))
+                || *t == Tile::Wind(ctx.seat_wind)
+                || *t == Tile::Wind(ctx.prevailing_wind)
+        };
+
+        if closed && chis.len() == 4 && !is_yakuhai(&eye.tile) && wait == Wait::TwoSided {
+            yaku.push(Yaku::Pinfu);
+        }
+
+        if closed {
+            let mut identical = 0;
+            let mut counted = vec![false; chis.len()];
+            for i in 0..chis.len() {
+                for j in i + 1..chis.len() {
+                    if !counted[i] && !counted[j] && chis[i].tile == chis[j].tile {
+                        counted[i] = true;
+                        counted[j] = true;
+                        identical += 1;
+                    }
+                }
+            }
+            match identical {
+                2 => yaku.push(Yaku::Ryanpeikou),
+                1 => yaku.push(Yaku::Iipeikou),
+                _ => (),
+            }
+        }
+
+        for pon in pons.iter() {
+            match pon.tile {
+                Tile::Dragon(Dragon::Baiban) => yaku.push(Yaku::Haku),
+                Tile::Dragon(Dragon::Fa) => yaku.push(Yaku::Hatsu),
+                Tile::Dragon(Dragon::Zhong) => yaku.push(Yaku::Chun),
+                _ => (),
+            }
+            if pon.tile == Tile::Wind(ctx.seat_wind) {
+                yaku.push(Yaku::SeatWind);
+            }
+            if pon.tile == Tile::Wind(ctx.prevailing_wind) {
+                yaku.push(Yaku::RoundWind);
+            }
+        }
+
+        let same_in_three_suits = |gs: &[&Group]| {
+            gs.iter().any(|g| {
+                let Tile::Wan(v) = g.tile else {
+                    return false;
+                };
+                gs.iter().any(|o| o.tile == Tile::Suo(v))
+                    && gs.iter().any(|o| o.tile == Tile::Tong(v))
+            })
+        };
+        if same_in_three_suits(&chis) {
+            yaku.push(Yaku::Sanshoku);
+        }
+        if same_in_three_suits(&pons) {
+            yaku.push(Yaku::SanshokuDoukou);
+        }
+
+        for suit in [Tile::Wan, Tile::Suo, Tile::Tong] {
+            if [TileValue::One, TileValue::Four, TileValue::Seven]
+                .iter()
+                .all(|v| chis.iter().any(|g| g.tile == suit(*v)))
+            {
+                yaku.push(Yaku::Ittsu);
+            }
+        }
+
+        if !chis.is_empty() && groups.iter().all(|g| g.has_terminal_or_honour()) {
+            match groups.iter().flat_map(|g| g.tiles()).any(|t| t.is_honour()) {
+                true => yaku.push(Yaku::Chanta),
+                false => yaku.push(Yaku::Junchan),
+            }
+        }
+
+        if pons.len() == 4 {
+            yaku.push(Yaku::Toitoi);
+        }
+        match pons.iter().filter(|g| g.concealed).count() {
+            4 => yaku.push(Yaku::Suuankou),
+            3 => yaku.push(Yaku::Sanankou),
+            _ => (),
+        }
+        match pons.iter().filter(|g| g.is_kan()).count() {
+            4 => yaku.push(Yaku::Suukantsu),
+            3 => yaku.push(Yaku::Sankantsu),
+            _ => (),
+        }
+
+        let dragon_pons = pons
+            .iter()
+            .filter(|g| matches!(g.tile, Tile::Dragon(_)))
+            .count();
+        match (dragon_pons, eye.tile) {
+            (3, _) => yaku.push(Yaku::Daisangen),
+            (2, Tile::Dragon(_)) => yaku.push(Yaku::Shousangen),
+            _ => (),
+        }
+        let wind_pons = pons
+            .iter()
+            .filter(|g| matches!(g.tile, Tile::Wind(_)))
+            .count();
+        match (wind_pons, eye.tile) {
+            (4, _) => yaku.push(Yaku::Daisuushii),
+            (3, Tile::Wind(_)) => yaku.push(Yaku::Shousuushii),
+            _ => (),
+        }
+        yaku
+    }
+
+    fn fu(&self, groups: &[Group], ctx: &WinContext, closed: bool, wait: Wait, pinfu: bool) -> u8 {
+        if pinfu {
+            return match ctx.self_drawn {
+                true => 20,
+                false => 30,
+            };
+        }
+        let mut fu = 20;
+        if closed && !ctx.self_drawn {
+            fu += 10;
+        }
+        if ctx.self_drawn {
+            fu += 2;
+        }
+        for group in groups.iter() {
+            if group.is_pon() {
+                let mut set_fu = 2;
+                if group.has_terminal_or_honour() {
+                    set_fu *= 2;
+                }
+                if group.concealed {
+                    set_fu *= 2;
+                }
+                if group.is_kan() {
+                    set_fu *= 4;
+                }
+                fu += set_fu;
+            } else if group.meld_type == MeldType::Eye {
+                if matches!(group.tile, Tile::Dragon(_)) {
+                    fu += 2;
+                }
+                if group.tile == Tile::Wind(ctx.seat_wind) {
+                    fu += 2;
+                }
+                if group.tile == Tile::Wind(ctx.prevailing_wind) {
+                    fu += 2;
+                }
+            }
+        }
+        if matches!(wait, Wait::Closed | Wait::Edge | Wait::SingleEye) {
+            fu += 2;
+        }
+        if !closed && fu == 20 {
+            fu = 30;
+        }
+        round_up(fu, 10) as u8
+    }
+
+    // Points each opponent pays to the winner. Honba go to any winner, 100 from every payer
+    // on a self-drawn win and 300 from a discarder.
+    pub fn payments(&self, hu: &RiichiHu, ctx: &WinContext) -> Vec<(Wind, u32)> {
+        let basic = hu.basic_points();
+        let honba = Self::HONBA * ctx.dealer_streak as u32;
+        ctx.seats
+            .iter()
+            .copied()
+            .filter(|w| w != &ctx.seat_wind)
+            .filter_map(|w| {
+                let multiplier = match (ctx.self_drawn, ctx.is_dealer()) {
+                    (false, _) if ctx.discarder != Some(w) => return None,
+                    (false, true) => 6,
+                    (false, false) => 4,
+                    (true, true) => 2,
+                    (true, false) if ctx.dealer == Some(w) => 2,
+                    (true, false) => 1,
+                };
+                let honba = match ctx.self_drawn {
+                    true => honba,
+                    false => honba * 3,
+                };
+                Some((w, round_up(basic * multiplier, 100) + honba))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::assert_err;
+
+    use crate::{
+        context::WinContext,
+        hand::Hand,
+        meld::{Meld, MeldType},
+        tile::{Dragon, Tile, TileValue, Wind},
+    };
+
+    use super::{dora_from_indicator, DeadWall, Riichi, RiichiPlayer, Yaku};
+
+    fn hand_from(tiles: &[Tile]) -> Hand {
+        let mut hand = Hand::new();
+        for tile in tiles {
+            hand.draw(tile);
+        }
+        hand
+    }
+
+    fn wan(v: TileValue) -> Tile {
+        Tile::Wan(v)
+    }
+
+    fn suo(v: TileValue) -> Tile {
+        Tile::Suo(v)
+    }
+
+    fn tong(v: TileValue) -> Tile {
+        Tile::Tong(v)
+    }
+
+    fn dead_wall(indicator: Tile) -> DeadWall {
+        let mut tiles = vec![Tile::Wind(Wind::North); 14];
+        tiles[4] = indicator;
+        tiles[9] = Tile::Dragon(Dragon::Zhong);
+        DeadWall::new(tiles).unwrap()
+    }
+
+    // 234m 567m 345p 678s + 2p, waiting on 2p or 5p
+    fn tenpai_hand() -> Hand {
+        use TileValue::*;
+        hand_from(&[
+            wan(Two),
+            wan(Three),
+            wan(Four),
+            wan(Five),
+            wan(Six),
+            wan(Seven),
+            tong(Three),
+            tong(Four),
+            suo(Six),
+            suo(Seven),
+            suo(Eight),
+            tong(Eight),
+            tong(Eight),
+        ])
+    }
+
+    #[test]
+    fn test_dora_from_indicator() {
+        assert_eq!(
+            dora_from_indicator(&Tile::Wan(TileValue::Nine)),
+            Tile::Wan(TileValue::One)
+        );
+        assert_eq!(
+            dora_from_indicator(&Tile::Wind(Wind::North)),
+            Tile::Wind(Wind::East)
+        );
+        assert_eq!(
+            dora_from_indicator(&Tile::Dragon(Dragon::Zhong)),
+            Tile::Dragon(Dragon::Baiban)
+        );
+        assert_eq!(
+            dora_from_indicator(&Tile::Suo(TileValue::RedFive)),
+            Tile::Suo(TileValue::Six)
+        );
+    }
+
+    #[test]
+    fn test_dead_wall() {
+        assert_err!(DeadWall::new(vec![]));
+        let mut wall = dead_wall(Tile::Wan(TileValue::One));
+        assert_eq!(wall.dora_indicators().len(), 1);
+        assert!(wall.draw_replacement().is_some());
+        assert_eq!(wall.dora_indicators().len(), 2);
+        assert_eq!(wall.ura_dora_indicators().len(), 2);
+    }
+
+    #[test]
+    fn test_riichi_tsumo_pinfu_tanyao() {
+        let mut player = RiichiPlayer::new(tenpai_hand());
+        player.draw(&Tile::Wind(Wind::West));
+        player
+            .declare_riichi(&Tile::Wind(Wind::West), false)
+            .unwrap();
+        assert_eq!(
+            player.waits(),
+            vec![Tile::Tong(TileValue::Two), Tile::Tong(TileValue::Five)]
+        );
+        player.draw(&Tile::Tong(TileValue::RedFive));
+
+        let ctx = WinContext::self_drawn(Tile::Tong(TileValue::RedFive), Wind::South, Wind::East)
+            .with_dealer_seat(Wind::East);
+        let hu = Riichi::default()
+            .search_hu(&player, &ctx, &dead_wall(Tile::Wan(TileValue::Three)))
+            .unwrap();
+        assert_eq!(
+            hu.yaku(),
+            &vec![
+                Yaku::Riichi,
+                Yaku::Ippatsu,
+                Yaku::MenzenTsumo,
+                Yaku::Pinfu,
+                Yaku::Tanyao
+            ]
+        );
+        assert_eq!((hu.dora(), hu.ura_dora(), hu.aka_dora()), (1, 0, 1));
+        assert_eq!((hu.han(), hu.fu()), (7, 20));
+        assert_eq!(hu.basic_points(), 3000);
+        assert_eq!(
+            Riichi::default().payments(&hu, &ctx),
+            vec![(Wind::East, 6000), (Wind::West, 3000), (Wind::North, 3000)]
+        );
+        // The dealer pays double wherever they sit, and every payer adds one honba
+        let ctx = ctx.with_dealer_seat(Wind::West).with_dealer_streak(1);
+        assert_eq!(
+            Riichi::default().payments(&hu, &ctx),
+            vec![(Wind::East, 3100), (Wind::West, 6100), (Wind::North, 3100)]
+        );
+    }
+
+    #[test]
+    fn test_riichi_requires_closed_tenpai() {
+        let mut player = RiichiPlayer::new(tenpai_hand());
+        player.draw(&Tile::Wind(Wind::West));
+        assert_err!(player.declare_riichi(&Tile::Tong(TileValue::Three), false));
+        assert!(player.riichi().is_none());
+    }
+
+    #[test]
+    fn test_furiten() {
+        let mut player = RiichiPlayer::new(tenpai_hand());
+        player.draw(&Tile::Tong(TileValue::Two));
+        player.discard(&Tile::Tong(TileValue::Two)).unwrap();
+        assert!(player.is_furiten());
+
+        let ctx = WinContext::discard(
+            Tile::Tong(TileValue::Five),
+            Wind::West,
+            Wind::South,
+            Wind::East,
+        );
+        let riichi = Riichi::default();
+        assert_eq!(
+            riichi.search_hu(&player, &ctx, &dead_wall(Tile::Wan(TileValue::Three))),
+            None
+        );
+
+        let mut player = RiichiPlayer::new(tenpai_hand());
+        assert!(!player.is_furiten());
+        player.pass_ron(&Tile::Tong(TileValue::Five));
+        assert!(player.is_furiten());
+        player.draw(&Tile::Wind(Wind::West));
+        assert!(!player.is_furiten());
+    }
+
+    #[test]
+    fn test_ron_fu_and_points() {
+        use TileValue::*;
+        // 123m 789m 456p + 9p, ron on 9p tanki with an open chun pon
+        let mut hand = hand_from(&[
+            wan(One),
+            wan(Two),
+            wan(Three),
+            wan(Seven),
+            wan(Eight),
+            wan(Nine),
+            tong(Four),
+            tong(Five),
+            tong(Six),
+            Tile::Dragon(Dragon::Zhong),
+            Tile::Dragon(Dragon::Zhong),
+            tong(Nine),
+        ]);
+        hand.meld(
+            Meld::new(
+                vec![Tile::Dragon(Dragon::Zhong); 2],
+                Some(Tile::Dragon(Dragon::Zhong)),
+                MeldType::Pong,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let player = RiichiPlayer::new(hand);
+        let ctx = WinContext::discard(Tile::Tong(Nine), Wind::North, Wind::South, Wind::East);
+        let hu = Riichi::default()
+            .search_hu(&player, &ctx, &dead_wall(Tile::Wind(Wind::North)))
+            .unwrap();
+        assert_eq!(hu.yaku(), &vec![Yaku::Chun]);
+        // 20 + 4 for the open dragon pon + 2 for the tanki wait
+        assert_eq!((hu.han(), hu.fu()), (1, 30));
+        assert_eq!(
+            Riichi::default().payments(&hu, &ctx),
+            vec![(Wind::North, 1000)]
+        );
+    }
+
+    #[test]
+    fn test_no_yaku() {
+        use TileValue::*;
+        let mut hand = hand_from(&[
+            wan(One),
+            wan(Two),
+            wan(Three),
+            wan(Seven),
+            wan(Eight),
+            suo(Two),
+            suo(Three),
+        ]);
+        hand.meld(Meld::new(vec![suo(Two), suo(Three)], Some(suo(Four)), MeldType::Chi).unwrap())
+            .unwrap();
+        for tile in [tong(Four), tong(Five), tong(Six), tong(Nine)] {
+            hand.draw(&tile);
+        }
+        hand.draw(&tong(Nine));
+        let player = RiichiPlayer::new(hand);
+        let ctx = WinContext::discard(wan(Nine), Wind::North, Wind::South, Wind::East);
+        assert_eq!(
+            Riichi::default().search_hu(&player, &ctx, &dead_wall(wan(Eight))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_yakuman() {
+        use TileValue::*;
+        let hand = hand_from(&[
+            Tile::Dragon(Dragon::Zhong),
+            Tile::Dragon(Dragon::Zhong),
+            Tile::Dragon(Dragon::Zhong),
+            Tile::Dragon(Dragon::Fa),
+            Tile::Dragon(Dragon::Fa),
+            Tile::Dragon(Dragon::Fa),
+            Tile::Dragon(Dragon::Baiban),
+            Tile::Dragon(Dragon::Baiban),
+            Tile::Dragon(Dragon::Baiban),
+            wan(Two),
+            wan(Three),
+            wan(Four),
+            tong(One),
+        ]);
+        let player = RiichiPlayer::new(hand);
+        let ctx = WinContext::discard(tong(One), Wind::North, Wind::East, Wind::East).with_dealer();
+        let hu = Riichi::default()
+            .search_hu(&player, &ctx, &dead_wall(wan(One)))
+            .unwrap();
+        assert_eq!(hu.yaku(), &vec![Yaku::Daisangen]);
+        assert_eq!(
+            Riichi::default().payments(&hu, &ctx),
+            vec![(Wind::North, 48000)]
+        );
+        // Two honba, paid for the whole table by the discarder
+        assert_eq!(
+            Riichi::default().payments(&hu, &ctx.with_dealer_streak(2)),
+            vec![(Wind::North, 48600)]
+        );
+    }
+}
@@ -0,0 +1,972 @@
+use crate::{
+    claim::{ClaimOption, KongOption, RelativeSeat},
+    context::{Replacement, WinContext},
+    error::MahjongError,
+    hand::{validate_table, Hand},
+    meld::{Meld, MeldType, Provenance},
+    rules::RuleSet,
+    tile::{Suit, Tile, Wind},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KongKind {
+    // Claimed from a discard
+    Exposed,
+    // Drawn fourth tile added to an exposed pong
+    Added,
+    Concealed,
+}
+
+// What the player to act has to do next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Draw,
+    // A replacement tile from the back of the wall after declaring a kong
+    Replacement,
+    Discard,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PaymentReason {
+    Win,
+    Kong(KongKind),
+    // The whole hand paid by whoever fed the set completing a limit hand
+    Liability,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub from: Wind,
+    pub to: Wind,
+    pub amount: u32,
+    pub reason: PaymentReason,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Ledger(Vec<Payment>);
+
+impl Ledger {
+    pub fn record(&mut self, from: Wind, to: Wind, amount: u32, reason: PaymentReason) {
+        self.0.push(Payment {
+            from,
+            to,
+            amount,
+            reason,
+        });
+    }
+
+    pub fn payments(&self) -> &Vec<Payment> {
+        &self.0
+    }
+
+    // Net amount received by the seat over every payment so far
+    pub fn balance(&self, seat: Wind) -> i64 {
+        self.0
+            .iter()
+            .map(|p| match (p.to == seat, p.from == seat) {
+                (true, false) => p.amount as i64,
+                (false, true) => -(p.amount as i64),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Player {
+    seat: Wind,
+    hand: Hand,
+    won: bool,
+}
+
+impl Player {
+    pub fn seat(&self) -> Wind {
+        self.seat
+    }
+
+    pub fn hand(&self) -> &Hand {
+        &self.hand
+    }
+
+    pub fn has_won(&self) -> bool {
+        self.won
+    }
+}
+
+// Chips each payer owes the moment a kong is declared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KongPoints {
+    pub exposed: u32,
+    pub added: u32,
+    pub concealed: u32,
+}
+
+impl KongPoints {
+    pub const SINGAPORE: KongPoints = KongPoints {
+        exposed: 1,
+        added: 1,
+        concealed: 2,
+    };
+
+    pub fn points(&self, kind: KongKind) -> u32 {
+        match kind {
+            KongKind::Exposed => self.exposed,
+            KongKind::Added => self.added,
+            KongKind::Concealed => self.concealed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundRules {
+    pub hand_size: usize,
+    // The hand ends once this many players have won
+    pub winners_to_end: usize,
+    pub chi: bool,
+    // Bao, feeding a third dragon or fourth wind pong makes the feeder pay for the whole hand
+    pub liability: bool,
+    pub kong_points: KongPoints,
+}
+
+impl Default for RoundRules {
+    fn default() -> Self {
+        Self {
+            hand_size: 13,
+            winners_to_end: 1,
+            chi: true,
+            liability: true,
+            kong_points: KongPoints::SINGAPORE,
+        }
+    }
+}
+
+// Who deals and which wind prevails over a game of any number of players. Players keep their
+// index while seat winds move with the dealer, who always sits East.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rotation {
+    players: usize,
+    dealer: usize,
+    // Index into Wind::ALL
+    prevailing: usize,
+    // Hands in a row the dealer has won or kept the deal
+    streak: u8,
+}
+
+impl Rotation {
+    pub fn new(players: usize) -> Result<Self, MahjongError> {
+        match players {
+            2..=4 => Ok(Self {
+                players,
+                dealer: 0,
+                prevailing: 0,
+                streak: 0,
+            }),
+            _ => Err(MahjongError::InvalidSeatsError(players)),
+        }
+    }
+
+    // Seat winds in play, in turn order starting from the dealer
+    pub fn seats(&self) -> Vec<Wind> {
+        Wind::ALL[..self.players].to_vec()
+    }
+
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub fn prevailing_wind(&self) -> Wind {
+        Wind::ALL[self.prevailing]
+    }
+
+    pub fn streak(&self) -> u8 {
+        self.streak
+    }
+
+    pub fn seat_of(&self, player: usize) -> Wind {
+        Wind::ALL[(player + self.players - self.dealer) % self.players]
+    }
+
+    pub fn player_at(&self, seat: Wind) -> Option<usize> {
+        let index = Wind::ALL.iter().position(|w| *w == seat)?;
+        match index < self.players {
+            true => Some((index + self.dealer) % self.players),
+            false => None,
+        }
+    }
+
+    // The dealer keeps the deal after winning or a drawn hand, otherwise it passes on and the
+    // prevailing wind moves once every player has dealt
+    pub fn next_hand(&mut self, dealer_keeps: bool) {
+        if dealer_keeps {
+            self.streak += 1;
+            return;
+        }
+        self.streak = 0;
+        self.dealer = (self.dealer + 1) % self.players;
+        if self.dealer == 0 {
+            self.prevailing += 1;
+        }
+    }
+
+    // A game lasts one round of each prevailing wind, one per player
+    pub fn is_over(&self) -> bool {
+        self.prevailing >= self.players
+    }
+
+    pub fn new_round(&self, wall: Vec<Tile>, rules: RoundRules) -> Round {
+        Round::new(&self.seats(), wall, self.prevailing_wind(), rules)
+            .with_dealer_streak(self.streak)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Round {
+    players: Vec<Player>,
+    wall: Vec<Tile>,
+    prevailing_wind: Wind,
+    // Index into players of whoever is to act next
+    current: usize,
+    last_discard: Option<(Wind, Tile)>,
+    // The last tile drawn and whether it replaced a kong
+    last_draw: Option<(Tile, bool)>,
+    winners: Vec<Wind>,
+    rules: RoundRules,
+    // Hands in a row the dealer has won or kept the deal before this one
+    dealer_streak: u8,
+    // Discards made so far
+    turn: u32,
+    // A kong has been declared and its replacement tile not drawn yet
+    replacement_due: bool,
+    ledger: Ledger,
+}
+
+impl Round {
+    // Deals the hand to each seat in turn from the front of the wall, the first seat being the
+    // dealer who draws first
+    pub fn new(
+        seats: &[Wind],
+        mut wall: Vec<Tile>,
+        prevailing_wind: Wind,
+        rules: RoundRules,
+    ) -> Self {
+        let mut players: Vec<Player> = seats
+            .iter()
+            .map(|seat| Player {
+                seat: *seat,
+                hand: Hand::new(),
+                won: false,
+            })
+            .collect();
+        wall.reverse();
+        for _ in 0..rules.hand_size {
+            for player in players.iter_mut() {
+                if let Some(tile) = wall.pop() {
+                    player.hand.draw(&tile);
+                }
+            }
+        }
+        wall.reverse();
+        Self {
+            players,
+            wall,
+            prevailing_wind,
+            current: 0,
+            last_discard: None,
+            last_draw: None,
+            winners: Vec::new(),
+            rules,
+            dealer_streak: 0,
+            turn: 0,
+            replacement_due: false,
+            ledger: Ledger::default(),
+        }
+    }
+
+    pub fn with_dealer_streak(mut self, streak: u8) -> Self {
+        self.dealer_streak = streak;
+        self
+    }
+
+    pub fn dealer(&self) -> Wind {
+        self.players[0].seat
+    }
+
+    pub fn dealer_streak(&self) -> u8 {
+        self.dealer_streak
+    }
+
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    pub fn current(&self) -> Wind {
+        self.players[self.current].seat
+    }
+
+    pub fn player(&self, seat: Wind) -> Option<&Player> {
+        self.players.iter().find(|p| p.seat == seat)
+    }
+
+    pub fn players(&self) -> &Vec<Player> {
+        &self.players
+    }
+
+    // Seats still playing the hand, in turn order
+    pub fn active_seats(&self) -> Vec<Wind> {
+        self.players
+            .iter()
+            .filter(|p| !p.won)
+            .map(|p| p.seat)
+            .collect()
+    }
+
+    pub fn prevailing_wind(&self) -> Wind {
+        self.prevailing_wind
+    }
+
+    pub fn wall(&self) -> &Vec<Tile> {
+        &self.wall
+    }
+
+    pub fn last_discard(&self) -> Option<(Wind, Tile)> {
+        self.last_discard
+    }
+
+    pub fn winners(&self) -> &Vec<Wind> {
+        &self.winners
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    // A player holding one tile more than a full hand has drawn and must discard
+    pub fn phase(&self) -> Phase {
+        if self.replacement_due {
+            return Phase::Replacement;
+        }
+        match self.players[self.current].hand.size() > self.rules.hand_size {
+            true => Phase::Discard,
+            false => Phase::Draw,
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winners.len() >= self.rules.winners_to_end || self.wall.is_empty()
+    }
+
+    fn seat_index(&self, seat: Wind) -> Result<usize, MahjongError> {
+        self.players
+            .iter()
+            .position(|p| p.seat == seat)
+            .ok_or(MahjongError::SeatNotAtTableError(seat))
+    }
+
+    // The index of a seat still playing the hand
+    fn index(&self, seat: Wind) -> Result<usize, MahjongError> {
+        let index = self.seat_index(seat)?;
+        match self.players[index].won {
+            true => Err(MahjongError::PlayerOutError(seat)),
+            false => Ok(index),
+        }
+    }
+
+    fn check_turn(&self, seat: Wind) -> Result<usize, MahjongError> {
+        let index = self.index(seat)?;
+        match index == self.current {
+            true => Ok(index),
+            false => Err(MahjongError::NotPlayersTurnError(seat)),
+        }
+    }
+
+    fn check_phase(&self, seat: Wind, phase: Phase) -> Result<usize, MahjongError> {
+        let index = self.check_turn(seat)?;
+        match self.phase() == phase {
+            true => Ok(index),
+            false => Err(MahjongError::WrongPhaseError(seat, self.phase())),
+        }
+    }
+
+    fn mark_claimed(&mut self, discarder: Wind, seat: Wind) -> Result<(), MahjongError> {
+        match self.players.iter_mut().find(|p| p.seat == discarder) {
+            Some(player) => player.hand.mark_claimed(seat),
+            None => Err(MahjongError::SeatNotAtTableError(discarder)),
+        }
+    }
+
+    // The next seat after index that has not left the hand
+    fn next_active(&self, index: usize) -> usize {
+        (1..=self.players.len())
+            .map(|i| (index + i) % self.players.len())
+            .find(|i| !self.players[*i].won)
+            .unwrap_or(index)
+    }
+
+    // The current player draws from the front of the wall
+    pub fn draw(&mut self) -> Result<Tile, MahjongError> {
+        let seat = self.current();
+        self.check_phase(seat, Phase::Draw)?;
+        let tile = match self.wall.is_empty() {
+            true => return Err(MahjongError::EmptyWallError),
+            false => self.wall.remove(0),
+        };
+        self.last_discard = None;
+        self.last_draw = Some((tile, false));
+        self.players[self.current].hand.draw(&tile);
+        Ok(tile)
+    }
+
+    // Replacement tiles after a kong are drawn from the back of the wall
+    pub fn draw_replacement(&mut self) -> Result<Tile, MahjongError> {
+        let seat = self.current();
+        self.check_phase(seat, Phase::Replacement)?;
+        let tile = self.wall.pop().ok_or(MahjongError::EmptyWallError)?;
+        self.replacement_due = false;
+        self.last_draw = Some((tile, true));
+        self.players[self.current].hand.draw(&tile);
+        Ok(tile)
+    }
+
+    pub fn discard(&mut self, seat: Wind, tile: &Tile) -> Result<(), MahjongError> {
+        let index = self.check_phase(seat, Phase::Discard)?;
+        self.players[index].hand.discard(tile)?;
+        self.turn += 1;
+        self.last_discard = Some((seat, *tile));
+        self.last_draw = None;
+        self.current = self.next_active(index);
+        Ok(())
+    }
+
+    // Claims the last discard into a meld, making it the claimer's turn
+    pub fn claim(&mut self, seat: Wind, meld: Meld) -> Result<(), MahjongError> {
+        let index = self.index(seat)?;
+        let discarder = match self.last_discard {
+            Some((discarder, tile))
+                if discarder != seat && meld.discarded_tile() == Some(&tile) =>
+            {
+                discarder
+            }
+            _ => {
+                return Err(MahjongError::InvalidClaimError(
+                    meld.discarded_tile().copied(),
+                ))
+            }
+        };
+        if meld.meld_type() == &MeldType::Chi && !self.rules.chi {
+            return Err(MahjongError::ClaimNotAllowedError(MeldType::Chi));
+        }
+        let seats: Vec<Wind> = self.players.iter().map(|p| p.seat).collect();
+        let provenance = Provenance {
+            seat: discarder,
+            from: RelativeSeat::of(discarder, seat, &seats).ok_or(
+                MahjongError::InvalidClaimError(meld.discarded_tile().copied()),
+            )?,
+            turn: self.turn,
+        };
+        if meld.meld_type() == &MeldType::Chi && provenance.from != RelativeSeat::Left {
+            return Err(MahjongError::ClaimNotAllowedError(MeldType::Chi));
+        }
+        let kong = meld.meld_type() == &MeldType::Gang;
+        self.players[index]
+            .hand
+            .meld(meld.with_provenance(provenance))?;
+        self.mark_claimed(discarder, seat)?;
+        self.last_discard = None;
+        self.current = index;
+        if kong {
+            self.replacement_due = true;
+            self.pay_kong(seat, KongKind::Exposed, &[discarder]);
+        }
+        Ok(())
+    }
+
+    // Every legal claim the seat has on the last discard, with hu checked by the rule set. The
+    // last discard of the hand can only be won.
+    pub fn claim_options<R: RuleSet>(
+        &self,
+        seat: Wind,
+        rules: &R,
+    ) -> Result<Vec<ClaimOption>, MahjongError> {
+        let index = self.index(seat)?;
+        let Some((discarder, tile)) = self.last_discard.filter(|(d, _)| *d != seat) else {
+            return Ok(Vec::new());
+        };
+        let hand = &self.players[index].hand;
+        let mut options = Vec::new();
+        if let Some(ctx) = self.win_context(seat) {
+            if rules.score(hand, &ctx).is_some() {
+                options.push(ClaimOption::Hu);
+            }
+        }
+        if self.wall.is_empty() {
+            return Ok(options);
+        }
+        let seats: Vec<Wind> = self.players.iter().map(|p| p.seat).collect();
+        let from = RelativeSeat::of(discarder, seat, &seats)
+            .ok_or(MahjongError::InvalidClaimError(Some(tile)))?;
+        options.extend(
+            hand.claim_options(&tile, from)?
+                .into_iter()
+                .filter(|o| self.rules.chi || !matches!(o, ClaimOption::Chi(_))),
+        );
+        Ok(options)
+    }
+
+    // Takes up one of the claim options on the last discard
+    pub fn apply_claim(&mut self, seat: Wind, option: ClaimOption) -> Result<(), MahjongError> {
+        match option {
+            ClaimOption::Hu => self.win(seat, false),
+            ClaimOption::Gang(meld) => {
+                let tile = meld
+                    .discarded_tile()
+                    .copied()
+                    .ok_or(MahjongError::InvalidClaimError(None))?;
+                self.kong(seat, KongKind::Exposed, &tile).map(|_| ())
+            }
+            ClaimOption::Pong(meld) | ClaimOption::Chi(meld) => self.claim(seat, meld),
+        }
+    }
+
+    // Declares a kong of the tile, returning who pays for it: the discarder of an exposed
+    // kong, otherwise every other seat still playing. Payments go into the ledger at once.
+    pub fn kong(
+        &mut self,
+        seat: Wind,
+        kind: KongKind,
+        tile: &Tile,
+    ) -> Result<Vec<Wind>, MahjongError> {
+        match kind {
+            KongKind::Exposed => {
+                let discarder = match self.last_discard {
+                    Some((discarder, discard)) if discard == *tile => discarder,
+                    _ => return Err(MahjongError::InvalidClaimError(Some(*tile))),
+                };
+                self.claim(
+                    seat,
+                    Meld::new(vec![*tile; 3], Some(*tile), MeldType::Gang)?,
+                )?;
+                Ok(vec![discarder])
+            }
+            KongKind::Added | KongKind::Concealed => {
+                let index = self.check_phase(seat, Phase::Discard)?;
+                match kind {
+                    KongKind::Added => self.players[index].hand.add_kong(tile)?,
+                    _ => self.players[index].hand.meld(Meld::new(
+                        vec![*tile; 4],
+                        None,
+                        MeldType::AnGang,
+                    )?)?,
+                }
+                let payers = self.others(seat);
+                self.replacement_due = true;
+                self.pay_kong(seat, kind, &payers);
+                Ok(payers)
+            }
+        }
+    }
+
+    // Every kong the seat can declare before discarding, none once the wall has no replacement
+    // tile left
+    pub fn kong_options(&self, seat: Wind) -> Result<Vec<KongOption>, MahjongError> {
+        let index = self.check_phase(seat, Phase::Discard)?;
+        match self.wall.is_empty() {
+            true => Ok(Vec::new()),
+            false => Ok(self.players[index].hand.kong_options(None)),
+        }
+    }
+
+    fn pay_kong(&mut self, seat: Wind, kind: KongKind, payers: &[Wind]) {
+        let points = self.rules.kong_points.points(kind);
+        if points == 0 {
+            return;
+        }
+        for payer in payers {
+            self.pay(*payer, seat, points, PaymentReason::Kong(kind));
+        }
+    }
+
+    fn others(&self, seat: Wind) -> Vec<Wind> {
+        self.active_seats()
+            .into_iter()
+            .filter(|s| *s != seat)
+            .collect()
+    }
+
+    // How the seat would win right now: on the last discard, or on its own last draw
+    pub fn win_context(&self, seat: Wind) -> Option<WinContext> {
+        let ctx = match (self.last_discard, self.last_draw) {
+            (Some((discarder, tile)), _) if discarder != seat => {
+                WinContext::discard(tile, discarder, seat, self.prevailing_wind)
+            }
+            (None, Some((tile, replacement))) if self.current() == seat => {
+                let ctx = WinContext::self_drawn(tile, seat, self.prevailing_wind);
+                match replacement {
+                    true => ctx.with_replacement(Replacement::Kong),
+                    false => ctx,
+                }
+            }
+            _ => return None,
+        };
+        let seats: Vec<Wind> = self.players.iter().map(|p| p.seat).collect();
+        let ctx = match self.wall.is_empty() {
+            true => ctx.with_last_tile(),
+            false => ctx,
+        }
+        .with_seats(&seats)
+        .with_dealer_seat(self.dealer())
+        .with_dealer_streak(self.dealer_streak);
+        Some(ctx)
+    }
+
+    // Checks every hand's size and that no tile appears more often than it exists between the
+    // hands, the wall and the last discard
+    pub fn validate(&self) -> Result<(), MahjongError> {
+        for player in self.players.iter() {
+            player.hand.validate(self.rules.hand_size)?;
+        }
+        let hands: Vec<&Hand> = self.players.iter().map(|p| &p.hand).collect();
+        let mut visible = self.wall.clone();
+        visible.extend(self.last_discard.map(|(_, tile)| tile));
+        validate_table(&hands, &visible)
+    }
+
+    // The seat leaves the hand as a winner, and play passes to the seat after it. A discard
+    // may be won by several seats before the next draw.
+    pub fn win(&mut self, seat: Wind, self_drawn: bool) -> Result<(), MahjongError> {
+        let index = match self_drawn {
+            true => self.check_phase(seat, Phase::Discard)?,
+            false => {
+                let index = self.index(seat)?;
+                match self.last_discard {
+                    Some((discarder, tile)) if discarder != seat => {
+                        self.players[index].hand.draw(&tile);
+                        self.mark_claimed(discarder, seat)?;
+                    }
+                    _ => return Err(MahjongError::InvalidClaimError(None)),
+                }
+                index
+            }
+        };
+        self.players[index].won = true;
+        self.winners.push(seat);
+        self.current = self.next_active(index);
+        Ok(())
+    }
+
+    pub fn pay(&mut self, from: Wind, to: Wind, amount: u32, reason: PaymentReason) {
+        self.ledger.record(from, to, amount, reason);
+    }
+
+    // Records what each seat owes the winner, unless a liable seat pays the whole amount
+    pub fn settle(&mut self, winner: Wind, payments: &[(Wind, u32)]) -> Result<(), MahjongError> {
+        let index = self.seat_index(winner)?;
+        let liable = liable_seat(self.players[index].hand.melds())
+            .filter(|seat| self.rules.liability && *seat != winner);
+        match liable {
+            Some(seat) => {
+                let amount = payments.iter().map(|(_, amount)| amount).sum();
+                self.pay(seat, winner, amount, PaymentReason::Liability);
+            }
+            None => {
+                for (seat, amount) in payments {
+                    self.pay(*seat, winner, *amount, PaymentReason::Win);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// The seat that fed the pong completing a third dragon or fourth wind set. Sets are exposed in
+// order, so the feeder is whoever was claimed from for the last of them.
+pub fn liable_seat(melds: &[Meld]) -> Option<Wind> {
+    [(Suit::Dragon, 3), (Suit::Wind, 4)]
+        .into_iter()
+        .find_map(|(suit, needed)| {
+            melds
+                .iter()
+                .filter(|m| {
+                    m.suit() == &suit && !matches!(m.meld_type(), MeldType::Chi | MeldType::Eye)
+                })
+                .nth(needed - 1)
+                .and_then(|m| m.fed_by())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_matches, assert_ok};
+
+    use crate::{
+        claim::{ClaimOption, RelativeSeat},
+        error::MahjongError,
+        meld::{Meld, MeldType, Orientation, Provenance},
+        tai::ScoreTai,
+        tile::{Dragon, Tile, TileValue, Wind},
+    };
+
+    use super::{liable_seat, KongKind, Ledger, PaymentReason, Phase, Rotation, Round, RoundRules};
+
+    fn bloody_battle() -> RoundRules {
+        RoundRules {
+            winners_to_end: 3,
+            ..Default::default()
+        }
+    }
+
+    fn wall() -> Vec<Tile> {
+        let mut wall = Vec::new();
+        let mut value = Some(TileValue::One);
+        while let Some(v) = value {
+            for _ in 0..4 {
+                wall.extend([Tile::Wan(v), Tile::Suo(v), Tile::Tong(v)]);
+            }
+            value = v.next();
+        }
+        wall
+    }
+
+    #[test]
+    fn test_deal_and_turns() {
+        let mut round = Round::new(&Wind::ALL, wall(), Wind::East, RoundRules::default());
+        assert!(round
+            .players()
+            .iter()
+            .all(|p| p.hand().concealed().values().sum::<u8>() == 13));
+        assert_eq!(round.wall().len(), 108 - 52);
+        assert_eq!(round.current(), Wind::East);
+        assert_eq!(round.phase(), Phase::Draw);
+        let held = *round
+            .player(Wind::East)
+            .unwrap()
+            .hand()
+            .concealed()
+            .keys()
+            .next()
+            .unwrap();
+        assert_matches!(
+            round.discard(Wind::East, &held),
+            Err(MahjongError::WrongPhaseError(Wind::East, Phase::Draw))
+        );
+        assert_err!(round.kong_options(Wind::East));
+        assert_err!(round.win(Wind::East, true));
+
+        let tile = round.draw().unwrap();
+        assert_eq!(round.phase(), Phase::Discard);
+        assert_matches!(
+            round.draw(),
+            Err(MahjongError::WrongPhaseError(Wind::East, Phase::Discard))
+        );
+        assert_err!(round.draw_replacement());
+        assert_err!(round.discard(Wind::South, &tile));
+        round.discard(Wind::East, &tile).unwrap();
+        assert_eq!(round.current(), Wind::South);
+        assert_eq!(round.last_discard(), Some((Wind::East, tile)));
+    }
+
+    #[test]
+    fn test_winners_leave_hand() {
+        let mut round = Round::new(&Wind::ALL, wall(), Wind::East, bloody_battle());
+        let tile = round.draw().unwrap();
+        round.discard(Wind::East, &tile).unwrap();
+        round.win(Wind::West, false).unwrap();
+        round.win(Wind::North, false).unwrap();
+        assert_eq!(round.active_seats(), vec![Wind::East, Wind::South]);
+        assert_eq!(round.current(), Wind::East);
+        assert!(!round.is_over());
+        assert_err!(round.discard(Wind::West, &tile));
+
+        round.draw().unwrap();
+        round.win(Wind::East, true).unwrap();
+        assert!(round.is_over());
+    }
+
+    #[test]
+    fn test_kong_payers() {
+        // The dealer holds four 1Wan
+        let mut tiles = wall();
+        tiles.swap(4, 3);
+        tiles.swap(8, 6);
+        tiles.swap(12, 9);
+        let mut round = Round::new(&Wind::ALL, tiles, Wind::East, bloody_battle());
+        let tile = Tile::Wan(TileValue::One);
+        assert_eq!(
+            round.player(Wind::East).unwrap().hand().concealed()[&tile],
+            4
+        );
+
+        assert_err!(round.kong(Wind::South, KongKind::Concealed, &tile));
+        round.draw().unwrap();
+        assert_eq!(
+            round.kong(Wind::East, KongKind::Concealed, &tile).unwrap(),
+            vec![Wind::South, Wind::West, Wind::North]
+        );
+        assert_eq!(round.ledger().balance(Wind::East), 6);
+        assert_eq!(round.ledger().balance(Wind::North), -2);
+        assert_matches!(
+            round.draw(),
+            Err(MahjongError::WrongPhaseError(
+                Wind::East,
+                Phase::Replacement
+            ))
+        );
+        assert_eq!(
+            round.draw_replacement().unwrap(),
+            Tile::Tong(TileValue::Nine)
+        );
+        assert_err!(round.draw_replacement());
+    }
+
+    #[test]
+    fn test_ledger_balance() {
+        let mut ledger = Ledger::default();
+        ledger.record(Wind::South, Wind::East, 4, PaymentReason::Win);
+        ledger.record(
+            Wind::East,
+            Wind::West,
+            2,
+            PaymentReason::Kong(KongKind::Exposed),
+        );
+        assert_eq!(ledger.balance(Wind::East), 2);
+        assert_eq!(ledger.balance(Wind::South), -4);
+        assert_eq!(ledger.balance(Wind::West), 2);
+        assert_eq!(ledger.balance(Wind::North), 0);
+    }
+
+    #[test]
+    fn test_rotation() {
+        assert_err!(Rotation::new(5));
+        let mut rotation = Rotation::new(3).unwrap();
+        assert_eq!(rotation.seats(), vec![Wind::East, Wind::South, Wind::West]);
+
+        rotation.next_hand(true);
+        rotation.next_hand(true);
+        assert_eq!(rotation.dealer(), 0);
+        assert_eq!(rotation.streak(), 2);
+        let round = rotation.new_round(wall(), RoundRules::default());
+        assert_eq!(round.dealer_streak(), 2);
+        assert_ok!(round.validate());
+        rotation.next_hand(false);
+        assert_eq!(rotation.dealer(), 1);
+        assert_eq!(rotation.streak(), 0);
+        assert_eq!(rotation.seat_of(1), Wind::East);
+        assert_eq!(rotation.seat_of(0), Wind::West);
+        assert_eq!(rotation.player_at(Wind::South), Some(2));
+        assert_eq!(rotation.player_at(Wind::North), None);
+        assert_eq!(rotation.prevailing_wind(), Wind::East);
+
+        rotation.next_hand(false);
+        rotation.next_hand(false);
+        assert_eq!(rotation.dealer(), 0);
+        assert_eq!(rotation.prevailing_wind(), Wind::South);
+        for _ in 0..6 {
+            rotation.next_hand(false);
+        }
+        assert!(rotation.is_over());
+    }
+
+    #[test]
+    fn test_liability() {
+        let dragons = [Dragon::Zhong, Dragon::Fa, Dragon::Baiban].map(Tile::Dragon);
+        let mut tiles = wall();
+        // East holds one of each dragon and South a pair of each
+        for (i, dragon) in dragons.iter().enumerate() {
+            tiles[i * 4] = *dragon;
+            tiles[i * 8 + 1] = *dragon;
+            tiles[i * 8 + 5] = *dragon;
+        }
+        let mut round = Round::new(&Wind::ALL, tiles, Wind::East, RoundRules::default());
+        for dragon in dragons {
+            round.draw().unwrap();
+            round.discard(Wind::East, &dragon).unwrap();
+            let pong = Meld::new(vec![dragon; 2], Some(dragon), MeldType::Pong).unwrap();
+            round.claim(Wind::South, pong).unwrap();
+            let hand = round.player(Wind::South).unwrap().hand();
+            let tile = *hand.concealed().keys().find(|t| !t.is_honour()).unwrap();
+            round.discard(Wind::South, &tile).unwrap();
+            for seat in [Wind::West, Wind::North] {
+                let tile = round.draw().unwrap();
+                round.discard(seat, &tile).unwrap();
+            }
+        }
+        let melds = round.player(Wind::South).unwrap().hand().melds();
+        assert_eq!(liable_seat(melds), Some(Wind::East));
+
+        let payments = [(Wind::East, 8), (Wind::West, 8), (Wind::North, 8)];
+        round.settle(Wind::South, &payments).unwrap();
+        assert_eq!(round.ledger().payments().len(), 1);
+        assert_eq!(
+            round.ledger().payments()[0].reason,
+            PaymentReason::Liability
+        );
+        assert_eq!(round.ledger().balance(Wind::East), -24);
+    }
+
+    #[test]
+    fn test_settle_after_win() {
+        let mut round = Round::new(&Wind::ALL, wall(), Wind::East, RoundRules::default());
+        let tile = round.draw().unwrap();
+        round.discard(Wind::East, &tile).unwrap();
+        round.win(Wind::South, false).unwrap();
+        round.settle(Wind::South, &[(Wind::East, 8)]).unwrap();
+        assert_eq!(round.ledger().balance(Wind::South), 8);
+        assert_eq!(round.ledger().payments()[0].reason, PaymentReason::Win);
+    }
+
+    #[test]
+    fn test_claim_options() {
+        let mut round = Round::new(&Wind::ALL, wall(), Wind::East, RoundRules::default());
+        let rules = ScoreTai::singapore();
+        let tile = round.draw().unwrap();
+        assert_eq!(tile, Tile::Suo(TileValue::Five));
+        round.discard(Wind::East, &tile).unwrap();
+
+        // West holds 3Suo and 4Suo too but only the next player may chi
+        assert_eq!(round.claim_options(Wind::West, &rules).unwrap(), vec![]);
+        assert_eq!(round.claim_options(Wind::East, &rules).unwrap(), vec![]);
+        let chi = Meld::new(
+            vec![Tile::Suo(TileValue::Three), Tile::Suo(TileValue::Four)],
+            Some(tile),
+            MeldType::Chi,
+        )
+        .unwrap();
+        let options = round.claim_options(Wind::South, &rules).unwrap();
+        assert_eq!(options, vec![ClaimOption::Chi(chi)]);
+
+        round.apply_claim(Wind::South, options[0].clone()).unwrap();
+        assert_eq!(round.current(), Wind::South);
+        let meld = &round.player(Wind::South).unwrap().hand().melds()[0];
+        assert_eq!(
+            meld.provenance(),
+            Some(&Provenance {
+                seat: Wind::East,
+                from: RelativeSeat::Left,
+                turn: 1,
+            })
+        );
+        assert_eq!(meld.layout()[0], (tile, Orientation::Sideways));
+        let river = round.player(Wind::East).unwrap().hand().river();
+        assert!(river[0].from_draw);
+        assert_eq!(river[0].claimed_by, Some(Wind::South));
+    }
+
+    #[test]
+    fn test_chi_only_from_left() {
+        let mut round = Round::new(&Wind::ALL, wall(), Wind::East, RoundRules::default());
+        let tile = round.draw().unwrap();
+        round.discard(Wind::East, &tile).unwrap();
+        let chi = Meld::new(
+            vec![Tile::Suo(TileValue::Three), Tile::Suo(TileValue::Four)],
+            Some(tile),
+            MeldType::Chi,
+        )
+        .unwrap();
+        // West holds the tiles for the chi but sits across from East
+        assert_matches!(
+            round.claim(Wind::West, chi.clone()),
+            Err(MahjongError::ClaimNotAllowedError(MeldType::Chi))
+        );
+        assert!(round.player(Wind::West).unwrap().hand().melds().is_empty());
+        assert_ok!(round.claim(Wind::South, chi));
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{
+    context::WinContext,
+    hand::Hand,
+    hu::{search_hus, Score},
+    meld::Meld,
+    tai::ScoreTai,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scoring<P> {
+    pub melds: Vec<Meld>,
+    pub patterns: Vec<P>,
+    pub value: u32,
+}
+
+pub trait RuleSet {
+    type Pattern: Copy + Eq + Ord + std::fmt::Debug;
+
+    // Every valid winning interpretation of the hand, best first
+    fn score_all(&self, hand: &Hand, ctx: &WinContext) -> Vec<Scoring<Self::Pattern>>;
+
+    fn score(&self, hand: &Hand, ctx: &WinContext) -> Option<Scoring<Self::Pattern>> {
+        self.score_all(hand, ctx).into_iter().next()
+    }
+}
+
+impl RuleSet for ScoreTai {
+    type Pattern = Score;
+
+    fn score_all(&self, hand: &Hand, ctx: &WinContext) -> Vec<Scoring<Score>> {
+        search_hus(hand, ctx, self)
+            .into_iter()
+            .map(|hu| Scoring {
+                melds: hu.melds().clone(),
+                patterns: hu.scores().clone(),
+                value: hu.tai() as u32,
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,555 @@
+use std::collections::HashMap;
+
+use crate::{
+    context::{Replacement, WinContext},
+    error::MahjongError,
+    hand::Hand,
+    hu::{search_melds, HandShape},
+    meld::{Meld, MeldType},
+    round::{KongKind, KongPoints, Round, RoundRules},
+    tile::{Suit, Tile, TileValue, Wind},
+};
+
+// Sichuan "bloody battle" fan patterns
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fan {
+    SelfDrawn,
+    // Every four of a kind in the hand, kongs included
+    Root,
+    WinOnKong,
+    RobbingKong,
+    LastTile,
+    AllPongs,
+    Terminals,
+    SevenPairs,
+    FullFlush,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SichuanHu {
+    melds: Vec<Meld>,
+    fans: Vec<Fan>,
+    fan: u8,
+}
+
+impl SichuanHu {
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn fans(&self) -> &Vec<Fan> {
+        &self.fans
+    }
+
+    pub fn fan(&self) -> u8 {
+        self.fan
+    }
+}
+
+impl std::cmp::Ord for SichuanHu {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.fan
+            .cmp(&other.fan)
+            .then_with(|| other.melds.cmp(&self.melds))
+            .then_with(|| other.fans.cmp(&self.fans))
+    }
+}
+
+impl std::cmp::PartialOrd for SichuanHu {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sichuan {
+    pub fan: HashMap<Fan, u8>,
+    pub cap: u8,
+    // Points for a hand without fan, doubled for every fan up to the cap
+    pub base: u32,
+}
+
+impl Default for Sichuan {
+    fn default() -> Self {
+        Self {
+            fan: HashMap::from([
+                (Fan::SelfDrawn, 1),
+                (Fan::Root, 1),
+                (Fan::WinOnKong, 1),
+                (Fan::RobbingKong, 1),
+                (Fan::LastTile, 1),
+                (Fan::AllPongs, 1),
+                (Fan::Terminals, 2),
+                (Fan::SevenPairs, 2),
+                (Fan::FullFlush, 2),
+            ]),
+            cap: 3,
+            base: 1,
+        }
+    }
+}
+
+impl Sichuan {
+    pub const SEATS: usize = 4;
+    // The hand ends once all but one player have won
+    pub const WINNERS_TO_END: usize = Self::SEATS - 1;
+
+    // Four of every numbered tile, without honours or bonus tiles
+    pub fn tiles() -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        let mut value = Some(TileValue::One);
+        while let Some(v) = value {
+            for _ in 0..4 {
+                tiles.extend([Tile::Wan(v), Tile::Suo(v), Tile::Tong(v)]);
+            }
+            value = v.next();
+        }
+        tiles
+    }
+
+    fn new_hu(&self, mut melds: Vec<Meld>, mut fans: Vec<Fan>) -> SichuanHu {
+        melds.sort();
+        fans.sort();
+        let fan = fans
+            .iter()
+            .map(|f| *self.fan.get(f).unwrap_or(&0) as u32)
+            .sum::<u32>()
+            .min(u8::MAX as u32) as u8;
+        SichuanHu { melds, fans, fan }
+    }
+
+    // Every interpretation of the hand, best first. A hand still holding its missing suit
+    // cannot win.
+    pub fn search_hus(&self, hand: &Hand, ctx: &WinContext, missing: Suit) -> Vec<SichuanHu> {
+        let mut concealed = hand.concealed().clone();
+        if let Some(tile) = ctx.claimed_tile() {
+            concealed.add_n(tile, 1);
+        }
+        let mut tiles: Vec<Tile> = hand.melds().iter().flat_map(|m| m.all_tiles()).collect();
+        for (tile, count) in concealed.iter() {
+            tiles.extend(vec![*tile; *count as usize]);
+        }
+        if tiles
+            .iter()
+            .any(|t| t.suit() == missing || !t.is_playable())
+        {
+            return Vec::new();
+        }
+
+        let mut hand_fans = Vec::new();
+        if ctx.self_drawn {
+            hand_fans.push(Fan::SelfDrawn);
+            if ctx.replacement == Some(Replacement::Kong) {
+                hand_fans.push(Fan::WinOnKong);
+            }
+        }
+        if ctx.robbing_kong {
+            hand_fans.push(Fan::RobbingKong);
+        }
+        if ctx.last_tile {
+            hand_fans.push(Fan::LastTile);
+        }
+        let mut suits: Vec<Suit> = tiles.iter().map(|t| t.suit()).collect();
+        suits.sort();
+        suits.dedup();
+        if suits.len() == 1 {
+            hand_fans.push(Fan::FullFlush);
+        }
+        let mut kinds = tiles.clone();
+        kinds.sort();
+        kinds.dedup();
+        for kind in kinds {
+            if tiles.iter().filter(|t| **t == kind).count() == 4 {
+                hand_fans.push(Fan::Root);
+            }
+        }
+
+        let mut hus = Vec::new();
+        if hand.melds().is_empty()
+            && concealed.values().sum::<u8>() == 14
+            && concealed.values().all(|c| c % 2 == 0)
+        {
+            let mut fans = hand_fans.clone();
+            fans.push(Fan::SevenPairs);
+            hus.push(self.new_hu(Vec::new(), fans));
+        }
+        for poss_meld in search_melds(&concealed, HandShape::STANDARD.concealed_sets(hand)) {
+            let mut melds = hand.melds().clone();
+            melds.extend(poss_meld);
+            let mut fans = hand_fans.clone();
+            if melds
+                .iter()
+                .all(|m| !matches!(m.meld_type(), MeldType::Chi))
+            {
+                fans.push(Fan::AllPongs);
+            }
+            if melds
+                .iter()
+                .all(|m| m.all_tiles().iter().any(|t| t.is_terminal()))
+            {
+                fans.push(Fan::Terminals);
+            }
+            hus.push(self.new_hu(melds, fans));
+        }
+
+        hus.sort_by(|a, b| b.cmp(a));
+        hus.dedup();
+        hus
+    }
+
+    pub fn search_hu(&self, hand: &Hand, ctx: &WinContext, missing: Suit) -> Option<SichuanHu> {
+        self.search_hus(hand, ctx, missing).into_iter().next()
+    }
+
+    pub fn points(&self, fan: u8) -> Result<u32, MahjongError> {
+        let doublings = fan.min(self.cap);
+        1_u32
+            .checked_shl(doublings as u32)
+            .and_then(|p| p.checked_mul(self.base))
+            .ok_or(MahjongError::PointsOverflowError(self.base, doublings))
+    }
+
+    // Wind for exposed and added kongs, rain for concealed kongs
+    pub fn kong_points(&self) -> KongPoints {
+        KongPoints {
+            exposed: self.base * 2,
+            added: self.base,
+            concealed: self.base * 2,
+        }
+    }
+}
+
+// A Sichuan hand played until three players have won or the wall runs out, settling every
+// win and kong as it happens
+#[derive(Debug)]
+pub struct SichuanRound {
+    rules: Sichuan,
+    round: Round,
+    missing: HashMap<Wind, Suit>,
+}
+
+impl SichuanRound {
+    pub fn new(rules: Sichuan, wall: Vec<Tile>, prevailing_wind: Wind) -> Self {
+        let kong_points = rules.kong_points();
+        Self {
+            rules,
+            round: Round::new(
+                &Wind::ALL,
+                wall,
+                prevailing_wind,
+                RoundRules {
+                    winners_to_end: Sichuan::WINNERS_TO_END,
+                    chi: false,
+                    kong_points,
+                    ..Default::default()
+                },
+            ),
+            missing: HashMap::new(),
+        }
+    }
+
+    pub fn round(&self) -> &Round {
+        &self.round
+    }
+
+    pub fn missing_suit(&self, seat: Wind) -> Option<Suit> {
+        self.missing.get(&seat).copied()
+    }
+
+    pub fn declare_missing_suit(&mut self, seat: Wind, suit: Suit) -> Result<(), MahjongError> {
+        if let Some(declared) = self.missing_suit(seat) {
+            return Err(MahjongError::MissingSuitDeclaredError(seat, declared));
+        }
+        match suit {
+            Suit::Wan | Suit::Suo | Suit::Tong => {
+                self.missing.insert(seat, suit);
+                Ok(())
+            }
+            _ => Err(MahjongError::TileNotPlayableError(suit)),
+        }
+    }
+
+    fn missing(&self, seat: Wind) -> Result<Suit, MahjongError> {
+        self.missing_suit(seat)
+            .ok_or(MahjongError::MissingSuitNotDeclaredError(seat))
+    }
+
+    fn holds_missing(&self, seat: Wind) -> Result<bool, MahjongError> {
+        let missing = self.missing(seat)?;
+        let player = self
+            .round
+            .player(seat)
+            .ok_or(MahjongError::SeatNotAtTableError(seat))?;
+        Ok(player
+            .hand()
+            .concealed()
+            .keys()
+            .any(|t| t.suit() == missing))
+    }
+
+    pub fn draw(&mut self) -> Result<Tile, MahjongError> {
+        self.round.draw()
+    }
+
+    // Tiles of the missing suit have to go before anything else
+    pub fn discard(&mut self, seat: Wind, tile: &Tile) -> Result<(), MahjongError> {
+        let missing = self.missing(seat)?;
+        if tile.suit() != missing && self.holds_missing(seat)? {
+            return Err(MahjongError::MissingSuitError(missing));
+        }
+        self.round.discard(seat, tile)
+    }
+
+    pub fn pong(&mut self, seat: Wind) -> Result<(), MahjongError> {
+        let Some((_, tile)) = self.round.last_discard() else {
+            return Err(MahjongError::InvalidClaimError(None));
+        };
+        let missing = self.missing(seat)?;
+        if tile.suit() == missing {
+            return Err(MahjongError::MissingSuitError(missing));
+        }
+        self.round
+            .claim(seat, Meld::new(vec![tile; 2], Some(tile), MeldType::Pong)?)
+    }
+
+    // Declares the kong, collects its payments and draws the replacement tile
+    pub fn kong(&mut self, seat: Wind, kind: KongKind, tile: &Tile) -> Result<Tile, MahjongError> {
+        let missing = self.missing(seat)?;
+        if tile.suit() == missing {
+            return Err(MahjongError::MissingSuitError(missing));
+        }
+        self.round.kong(seat, kind, tile)?;
+        self.round.draw_replacement()
+    }
+
+    // Scores the seat's win, collects from the discarder or from every player still in the
+    // hand, and takes the winner out of play
+    pub fn win(&mut self, seat: Wind) -> Result<SichuanHu, MahjongError> {
+        let missing = self.missing(seat)?;
+        let ctx = self
+            .round
+            .win_context(seat)
+            .ok_or(MahjongError::InvalidClaimError(None))?;
+        let hand = self
+            .round
+            .player(seat)
+            .ok_or(MahjongError::SeatNotAtTableError(seat))?
+            .hand();
+        let hu = self
+            .rules
+            .search_hu(hand, &ctx, missing)
+            .ok_or(MahjongError::NoHuError(seat))?;
+        let payers = match ctx.discarder {
+            Some(discarder) => vec![discarder],
+            None => self
+                .round
+                .active_seats()
+                .into_iter()
+                .filter(|s| *s != seat)
+                .collect(),
+        };
+        let points = self.rules.points(hu.fan)?;
+        self.round.win(seat, ctx.self_drawn)?;
+        let payments: Vec<(Wind, u32)> = payers.into_iter().map(|p| (p, points)).collect();
+        self.round.settle(seat, &payments)?;
+        Ok(hu)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.round.is_over()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_matches, assert_none};
+
+    use crate::{
+        context::WinContext,
+        error::MahjongError,
+        hand::Hand,
+        round::KongKind,
+        tile::{Suit, Tile, TileValue, Wind},
+    };
+
+    use super::{Fan, Sichuan, SichuanRound};
+
+    fn wan(v: TileValue) -> Tile {
+        Tile::Wan(v)
+    }
+
+    fn suo(v: TileValue) -> Tile {
+        Tile::Suo(v)
+    }
+
+    fn tong(v: TileValue) -> Tile {
+        Tile::Tong(v)
+    }
+
+    // Deals the hands in seat order, followed by the rest of the wall
+    fn wall_with(hands: [[Tile; 13]; 4], rest: &[Tile]) -> Vec<Tile> {
+        let mut wall = Vec::new();
+        for i in 0..13 {
+            wall.extend(hands.iter().map(|h| h[i]));
+        }
+        wall.extend(rest);
+        wall
+    }
+
+    #[test]
+    fn test_full_flush_pongs_capped() {
+        let mut hand = Hand::new();
+        for v in [TileValue::One, TileValue::Three, TileValue::Five] {
+            for _ in 0..3 {
+                hand.draw(&suo(v));
+            }
+        }
+        for tile in [
+            suo(TileValue::Seven),
+            suo(TileValue::Seven),
+            suo(TileValue::Seven),
+            suo(TileValue::Nine),
+            suo(TileValue::Nine),
+        ] {
+            hand.draw(&tile);
+        }
+        let ctx = WinContext::self_drawn(suo(TileValue::Seven), Wind::South, Wind::East);
+        let rules = Sichuan::default();
+        assert_none!(rules.search_hu(&hand, &ctx, Suit::Suo));
+
+        let hu = rules.search_hu(&hand, &ctx, Suit::Wan).unwrap();
+        assert_eq!(
+            hu.fans(),
+            &vec![Fan::SelfDrawn, Fan::AllPongs, Fan::FullFlush]
+        );
+        assert_eq!(hu.fan(), 4);
+        assert_eq!(rules.points(hu.fan()).unwrap(), 8);
+
+        let rules = Sichuan {
+            cap: 32,
+            ..Default::default()
+        };
+        assert_matches!(
+            rules.points(32),
+            Err(MahjongError::PointsOverflowError(1, 32))
+        );
+    }
+
+    #[test]
+    fn test_bloody_battle() {
+        let hands = [
+            [
+                wan(TileValue::One),
+                wan(TileValue::One),
+                wan(TileValue::One),
+                wan(TileValue::One),
+                wan(TileValue::Two),
+                wan(TileValue::Three),
+                wan(TileValue::Four),
+                wan(TileValue::Five),
+                wan(TileValue::Six),
+                wan(TileValue::Seven),
+                wan(TileValue::Eight),
+                wan(TileValue::Eight),
+                tong(TileValue::One),
+            ],
+            [
+                suo(TileValue::Two),
+                suo(TileValue::Two),
+                suo(TileValue::Two),
+                suo(TileValue::Three),
+                suo(TileValue::Three),
+                suo(TileValue::Three),
+                suo(TileValue::Four),
+                suo(TileValue::Four),
+                suo(TileValue::Four),
+                suo(TileValue::Five),
+                suo(TileValue::Five),
+                suo(TileValue::Five),
+                suo(TileValue::Six),
+            ],
+            [
+                tong(TileValue::Two),
+                tong(TileValue::Three),
+                tong(TileValue::Four),
+                tong(TileValue::Four),
+                tong(TileValue::Four),
+                wan(TileValue::Five),
+                wan(TileValue::Five),
+                wan(TileValue::Five),
+                wan(TileValue::Six),
+                wan(TileValue::Seven),
+                wan(TileValue::Eight),
+                wan(TileValue::Nine),
+                wan(TileValue::Nine),
+            ],
+            [
+                suo(TileValue::Seven),
+                suo(TileValue::Seven),
+                suo(TileValue::Seven),
+                suo(TileValue::Eight),
+                suo(TileValue::Eight),
+                suo(TileValue::Eight),
+                suo(TileValue::Nine),
+                suo(TileValue::Nine),
+                suo(TileValue::Nine),
+                tong(TileValue::Nine),
+                tong(TileValue::Nine),
+                tong(TileValue::Nine),
+                tong(TileValue::Two),
+            ],
+        ];
+        let wall = wall_with(
+            hands,
+            &[
+                wan(TileValue::Nine),
+                suo(TileValue::One),
+                tong(TileValue::Five),
+            ],
+        );
+        let mut round = SichuanRound::new(Sichuan::default(), wall, Wind::East);
+        let one_wan = wan(TileValue::One);
+        round.draw().unwrap();
+        assert_err!(round.kong(Wind::East, KongKind::Concealed, &one_wan));
+
+        for (seat, suit) in [
+            (Wind::East, Suit::Tong),
+            (Wind::South, Suit::Tong),
+            (Wind::West, Suit::Suo),
+            (Wind::North, Suit::Wan),
+        ] {
+            round.declare_missing_suit(seat, suit).unwrap();
+        }
+        assert_matches!(
+            round.declare_missing_suit(Wind::East, Suit::Wan),
+            Err(MahjongError::MissingSuitDeclaredError(
+                Wind::East,
+                Suit::Tong
+            ))
+        );
+        assert_eq!(round.missing_suit(Wind::East), Some(Suit::Tong));
+        assert_eq!(
+            round
+                .kong(Wind::East, KongKind::Concealed, &one_wan)
+                .unwrap(),
+            tong(TileValue::Five)
+        );
+        assert_err!(round.discard(Wind::East, &wan(TileValue::Nine)));
+        round.discard(Wind::East, &tong(TileValue::One)).unwrap();
+
+        let hu = round.win(Wind::West).unwrap();
+        assert_eq!(hu.fans(), &vec![]);
+        assert!(!round.is_over());
+        assert_eq!(
+            round.round().active_seats(),
+            vec![Wind::East, Wind::South, Wind::North]
+        );
+        assert_eq!(round.round().current(), Wind::North);
+
+        let ledger = round.round().ledger();
+        assert_eq!(ledger.balance(Wind::East), 5);
+        assert_eq!(ledger.balance(Wind::South), -2);
+        assert_eq!(ledger.balance(Wind::West), -1);
+        assert_eq!(ledger.balance(Wind::North), -2);
+    }
+}
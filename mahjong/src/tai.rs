@@ -0,0 +1,383 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use serde::{de::IntoDeserializer, Deserialize, Serialize};
+
+use crate::{error::MahjongError, hu::Score};
+
+const SINGAPORE: &str = include_str!("../tables/singapore.toml");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreTai {
+    tai: HashMap<Score, u8>,
+    limit: u8,
+    rules: LimitRules,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitRules {
+    pub cap: u8,
+    pub stack: HashSet<Score>,
+    pub multiple: MultipleLimits,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MultipleLimits {
+    // Only the limit score worth the most tai counts, on a tie the first in the order of Score
+    #[default]
+    Single,
+    // Every limit score counts, up to the cap
+    Stack,
+}
+
+#[derive(Deserialize)]
+struct TaiConfig {
+    limit: u8,
+    cap: Option<u8>,
+    #[serde(default)]
+    limit_hands: LimitHandsConfig,
+    tai: HashMap<String, u8>,
+}
+
+#[derive(Deserialize, Default)]
+struct LimitHandsConfig {
+    #[serde(default)]
+    stack: Vec<String>,
+    #[serde(default)]
+    multiple: MultipleLimits,
+}
+
+impl std::ops::Deref for ScoreTai {
+    type Target = HashMap<Score, u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tai
+    }
+}
+
+impl Default for ScoreTai {
+    fn default() -> Self {
+        Self::singapore()
+    }
+}
+
+impl ScoreTai {
+    pub fn new(
+        tai: HashMap<Score, u8>,
+        limit: u8,
+        rules: LimitRules,
+    ) -> Result<Self, MahjongError> {
+        if rules.cap < limit {
+            return Err(MahjongError::InvalidCapError(rules.cap, limit));
+        }
+        if let Some(score) = Score::ALL
+            .into_iter()
+            .find(|s| s.is_limit() && rules.stack.contains(s))
+        {
+            return Err(MahjongError::InvalidStackError(score));
+        }
+        for score in Score::ALL {
+            let value = *tai
+                .get(&score)
+                .ok_or(MahjongError::MissingScoreError(score))?;
+            if (score.is_limit() && value < limit) || (!score.is_limit() && value > limit) {
+                return Err(MahjongError::InvalidLimitError(score, value, limit));
+            }
+            if value > rules.cap {
+                return Err(MahjongError::ScoreAboveCapError(score, value, rules.cap));
+            }
+        }
+        Ok(Self { tai, limit, rules })
+    }
+
+    pub fn singapore() -> Self {
+        Self::from_toml(SINGAPORE).expect("bundled Singapore tai table is valid")
+    }
+
+    pub fn from_toml(s: &str) -> Result<Self, MahjongError> {
+        let config: TaiConfig =
+            toml::from_str(s).map_err(|e| MahjongError::TaiTableParseError(e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, MahjongError> {
+        let config: TaiConfig =
+            serde_json::from_str(s).map_err(|e| MahjongError::TaiTableParseError(e.to_string()))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MahjongError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&contents),
+            Some("json") => Self::from_json(&contents),
+            _ => Err(MahjongError::TaiTableParseError(format!(
+                "unsupported file extension for {}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn from_config(config: TaiConfig) -> Result<Self, MahjongError> {
+        let mut tai = HashMap::new();
+        for (name, value) in config.tai {
+            tai.insert(parse_score(name)?, value);
+        }
+        let rules = LimitRules {
+            cap: config.cap.unwrap_or(config.limit),
+            stack: config
+                .limit_hands
+                .stack
+                .into_iter()
+                .map(parse_score)
+                .collect::<Result<_, _>>()?,
+            multiple: config.limit_hands.multiple,
+        };
+        Self::new(tai, config.limit, rules)
+    }
+
+    // Applies the limit rules, returning the scores that count and their total tai
+    pub fn resolve(&self, scores: Vec<Score>) -> (Vec<Score>, u8) {
+        let mut limits: Vec<Score> = scores.iter().filter(|s| s.is_limit()).copied().collect();
+        let scores = match limits.is_empty() {
+            true => scores,
+            false => {
+                limits.sort();
+                limits.dedup();
+                if self.rules.multiple == MultipleLimits::Single {
+                    // Most tai first, then the earlier score, which compares greater here
+                    limits = limits
+                        .into_iter()
+                        .max_by(|a, b| self.tai(a).cmp(&self.tai(b)).then(b.cmp(a)))
+                        .into_iter()
+                        .collect();
+                }
+                limits
+                    .into_iter()
+                    .chain(scores.into_iter().filter(|s| self.rules.stack.contains(s)))
+                    .collect()
+            }
+        };
+        let tai = scores
+            .iter()
+            .map(|s| self.tai(s) as u32)
+            .sum::<u32>()
+            .min(self.rules.cap as u32) as u8;
+        (scores, tai)
+    }
+
+    pub fn tai(&self, score: &Score) -> u8 {
+        self.tai[score]
+    }
+
+    pub fn limit(&self) -> u8 {
+        self.limit
+    }
+
+    pub fn rules(&self) -> &LimitRules {
+        &self.rules
+    }
+}
+
+fn parse_score(name: String) -> Result<Score, MahjongError> {
+    Score::deserialize(name.as_str().into_deserializer())
+        .map_err(|_: serde::de::value::Error| MahjongError::UnknownScoreError(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_matches, assert_ok};
+
+    use crate::{error::MahjongError, hu::Score};
+
+    use super::{MultipleLimits, ScoreTai};
+
+    #[test]
+    fn test_singapore_table_complete() {
+        let score_tai = ScoreTai::singapore();
+        assert_eq!(score_tai.len(), Score::ALL.len());
+        assert_eq!(score_tai.limit(), 5);
+        assert_eq!(score_tai.tai(&Score::PingHu), 4);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let score_tai = ScoreTai::singapore();
+        let tai: serde_json::Map<String, serde_json::Value> = score_tai
+            .iter()
+            .map(|(s, t)| (format!("{:?}", s), (*t).into()))
+            .collect();
+        let json = serde_json::json!({
+            "limit": 5,
+            "limit_hands": {
+                "stack": score_tai.rules().stack.iter().map(|s| format!("{:?}", s)).collect::<Vec<_>>(),
+            },
+            "tai": tai,
+        })
+        .to_string();
+        assert_eq!(ScoreTai::from_json(&json).unwrap(), score_tai);
+    }
+
+    #[test]
+    fn test_unknown_score() {
+        let toml = include_str!("../tables/singapore.toml").to_owned() + "Chicken = 1\n";
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::UnknownScoreError(name)) if name == "Chicken"
+        );
+    }
+
+    #[test]
+    fn test_missing_score() {
+        let toml = include_str!("../tables/singapore.toml").replace("PingHu = 4\n", "");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::MissingScoreError(Score::PingHu))
+        );
+    }
+
+    #[test]
+    fn test_invalid_limit() {
+        let toml = include_str!("../tables/singapore.toml")
+            .replace("HiddenTreasure = 5", "HiddenTreasure = 4");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::InvalidLimitError(Score::HiddenTreasure, 4, 5))
+        );
+        let toml = include_str!("../tables/singapore.toml").replace("PingHu = 4", "PingHu = 6");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::InvalidLimitError(Score::PingHu, 6, 5))
+        );
+    }
+
+    #[test]
+    fn test_house_values() {
+        let toml = include_str!("../tables/singapore.toml").replace("AllPong = 2", "AllPong = 3");
+        let score_tai = assert_ok!(ScoreTai::from_toml(&toml));
+        assert_eq!(score_tai.tai(&Score::AllPong), 3);
+    }
+
+    #[test]
+    fn test_resolve_without_limit() {
+        let score_tai = ScoreTai::singapore();
+        assert_eq!(
+            score_tai.resolve(vec![Score::Dragon, Score::Dragon, Score::HalfFlush]),
+            (vec![Score::Dragon, Score::Dragon, Score::HalfFlush], 4)
+        );
+        assert_eq!(
+            score_tai.resolve(vec![Score::FullFlush, Score::AllPong]),
+            (vec![Score::FullFlush, Score::AllPong], 5)
+        );
+    }
+
+    #[test]
+    fn test_resolve_limit_keeps_stacking_scores() {
+        let score_tai = ScoreTai::singapore();
+        assert_eq!(
+            score_tai.resolve(vec![
+                Score::Animal,
+                Score::Dragon,
+                Score::PlayerFlower,
+                Score::HiddenTreasure
+            ]),
+            (
+                vec![Score::HiddenTreasure, Score::Animal, Score::PlayerFlower],
+                5
+            )
+        );
+    }
+
+    #[test]
+    fn test_resolve_highest_limit() {
+        let toml = include_str!("../tables/singapore.toml")
+            .replace("cap = 5", "cap = 12")
+            .replace("AllHonours = 5", "AllHonours = 10");
+        let score_tai = ScoreTai::from_toml(&toml).unwrap();
+        assert_eq!(
+            score_tai.resolve(vec![
+                Score::HiddenTreasure,
+                Score::AllHonours,
+                Score::Animal
+            ]),
+            (vec![Score::AllHonours, Score::Animal], 11)
+        );
+
+        // On a tie the score declared first in Score wins, whatever order they were found in
+        let tied =
+            ScoreTai::from_toml(&toml.replace("ThirteenWonders = 5", "ThirteenWonders = 10"))
+                .unwrap();
+        assert_eq!(
+            tied.resolve(vec![Score::ThirteenWonders, Score::AllHonours]),
+            (vec![Score::AllHonours], 10)
+        );
+
+        let toml = toml.replace("cap = 12", "cap = 8");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::ScoreAboveCapError(Score::AllHonours, 10, 8))
+        );
+    }
+
+    #[test]
+    fn test_resolve_multiple_limits() {
+        let scores = vec![Score::AllHonours, Score::Animal, Score::HiddenTreasure];
+        let score_tai = ScoreTai::singapore();
+        assert_eq!(
+            score_tai.resolve(scores.clone()),
+            (vec![Score::HiddenTreasure, Score::Animal], 5)
+        );
+
+        let toml = include_str!("../tables/singapore.toml")
+            .replace("cap = 5", "cap = 12")
+            .replace("multiple = \"Single\"", "multiple = \"Stack\"");
+        let score_tai = ScoreTai::from_toml(&toml).unwrap();
+        assert_eq!(score_tai.rules().multiple, MultipleLimits::Stack);
+        assert_eq!(
+            score_tai.resolve(scores.clone()),
+            (
+                vec![Score::HiddenTreasure, Score::AllHonours, Score::Animal],
+                11
+            )
+        );
+        assert_eq!(
+            score_tai.resolve(vec![
+                Score::HiddenTreasure,
+                Score::AllHonours,
+                Score::AllTerminals
+            ]),
+            (
+                vec![
+                    Score::HiddenTreasure,
+                    Score::AllTerminals,
+                    Score::AllHonours
+                ],
+                12
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_limit_hand_rules() {
+        let toml = include_str!("../tables/singapore.toml").replace("cap = 5", "cap = 4");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::InvalidCapError(4, 5))
+        );
+        let toml = include_str!("../tables/singapore.toml")
+            .replace("stack = [\"Animal\"", "stack = [\"AllHonours\", \"Animal\"");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::InvalidStackError(Score::AllHonours))
+        );
+        let toml = include_str!("../tables/singapore.toml")
+            .replace("stack = [\"Animal\"", "stack = [\"Chicken\"");
+        assert_matches!(
+            ScoreTai::from_toml(&toml),
+            Err(MahjongError::UnknownScoreError(name)) if name == "Chicken"
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+
+use crate::{
+    context::{Replacement, WinContext},
+    hand::Hand,
+    hu::{search_melds, HandShape},
+    meld::{Meld, MeldType},
+    rules::{RuleSet, Scoring},
+    tile::{Suit, Tile, Wind},
+};
+
+// Taiwanese 16-tile tai patterns
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Tai {
+    Dealer,
+    SelfDrawn,
+    Concealed,
+    ConcealedSelfDrawn,
+    SeatWind,
+    PrevailingWind,
+    DragonPong,
+    SeatFlower,
+    FlowerSet,
+    LastTile,
+    WinOnKong,
+    RobbingKong,
+    AllChows,
+    ThreeConcealedPongs,
+    AllPongs,
+    MixedOneSuit,
+    SmallThreeDragons,
+    FourConcealedPongs,
+    PureOneSuit,
+    GreatThreeDragons,
+    SmallFourWinds,
+    FiveConcealedPongs,
+    EightFlowers,
+    GreatFourWinds,
+    AllHonours,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaiwanHu {
+    melds: Vec<Meld>,
+    patterns: Vec<Tai>,
+    tai: u8,
+}
+
+impl TaiwanHu {
+    pub fn melds(&self) -> &Vec<Meld> {
+        &self.melds
+    }
+
+    pub fn patterns(&self) -> &Vec<Tai> {
+        &self.patterns
+    }
+
+    pub fn tai(&self) -> u8 {
+        self.tai
+    }
+}
+
+impl std::cmp::Ord for TaiwanHu {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.tai
+            .cmp(&other.tai)
+            .then_with(|| other.melds.cmp(&self.melds))
+            .then_with(|| other.patterns.cmp(&self.patterns))
+    }
+}
+
+impl std::cmp::PartialOrd for TaiwanHu {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Taiwanese {
+    pub tai: HashMap<Tai, u8>,
+    // Every payment is base + tai * per_tai
+    pub base: u32,
+    pub per_tai: u32,
+    // Tai added for each hand in a row the dealer has won or kept the deal
+    pub streak_tai: u32,
+}
+
+impl Default for Taiwanese {
+    fn default() -> Self {
+        Self {
+            tai: HashMap::from([
+                (Tai::Dealer, 1),
+                (Tai::SelfDrawn, 1),
+                (Tai::Concealed, 1),
+                (Tai::ConcealedSelfDrawn, 3),
+                (Tai::SeatWind, 1),
+                (Tai::PrevailingWind, 1),
+                (Tai::DragonPong, 1),
+                (Tai::SeatFlower, 1),
+                (Tai::FlowerSet, 2),
+                (Tai::LastTile, 1),
+                (Tai::WinOnKong, 1),
+                (Tai::RobbingKong, 1),
+                (Tai::AllChows, 2),
+                (Tai::ThreeConcealedPongs, 2),
+                (Tai::AllPongs, 4),
+                (Tai::MixedOneSuit, 4),
+                (Tai::SmallThreeDragons, 4),
+                (Tai::FourConcealedPongs, 5),
+                (Tai::PureOneSuit, 8),
+                (Tai::GreatThreeDragons, 8),
+                (Tai::SmallFourWinds, 8),
+                (Tai::FiveConcealedPongs, 8),
+                (Tai::EightFlowers, 8),
+                (Tai::GreatFourWinds, 16),
+                (Tai::AllHonours, 16),
+            ]),
+            base: 100,
+            per_tai: 20,
+            streak_tai: 2,
+        }
+    }
+}
+
+impl Taiwanese {
+    pub const SHAPE: HandShape = HandShape::SIXTEEN_TILE;
+
+    fn new_hu(&self, mut melds: Vec<Meld>, mut patterns: Vec<Tai>) -> TaiwanHu {
+        melds.sort();
+        patterns.sort();
+        let tai = patterns
+            .iter()
+            .map(|p| *self.tai.get(p).unwrap_or(&0) as u32)
+            .sum::<u32>()
+            .min(u8::MAX as u32) as u8;
+        TaiwanHu {
+            melds,
+            patterns,
+            tai,
+        }
+    }
+
+    // Every interpretation of the hand as five sets and an eye, best first
+    pub fn search_hus(&self, hand: &Hand, ctx: &WinContext) -> Vec<TaiwanHu> {
+        let mut concealed = hand.concealed().clone();
+        if let Some(tile) = ctx.claimed_tile() {
+            concealed.add_n(tile, 1);
+        }
+        let closed = hand.melds().iter().all(|m| m.is_concealed_set());
+
+        let mut hand_patterns = Vec::new();
+        if ctx.is_dealer() {
+            hand_patterns.push(Tai::Dealer);
+        }
+        match (ctx.self_drawn, closed) {
+            (true, true) => hand_patterns.push(Tai::ConcealedSelfDrawn),
+            (true, false) => hand_patterns.push(Tai::SelfDrawn),
+            (false, true) => hand_patterns.push(Tai::Concealed),
+            (false, false) => (),
+        }
+        if ctx.last_tile {
+            hand_patterns.push(Tai::LastTile);
+        }
+        if ctx.self_drawn && ctx.replacement == Some(Replacement::Kong) {
+            hand_patterns.push(Tai::WinOnKong);
+        }
+        if ctx.robbing_kong {
+            hand_patterns.push(Tai::RobbingKong);
+        }
+
+        let bonus = hand.bonus();
+        for _ in bonus.seat_flowers(ctx.seat_wind) {
+            hand_patterns.push(Tai::SeatFlower);
+        }
+        match bonus.complete_colours().len() {
+            2 => hand_patterns.push(Tai::EightFlowers),
+            sets => hand_patterns.extend(vec![Tai::FlowerSet; sets]),
+        }
+
+        let mut hus = Vec::new();
+        for poss_meld in search_melds(&concealed, Self::SHAPE.concealed_sets(hand)) {
+            let eye = poss_meld[0].clone();
+            // A pong completed by a discard is not concealed
+            let concealed_pongs = poss_meld
+                .iter()
+                .filter(|m| m.meld_type().eq(&MeldType::Pong))
+                .filter(|m| match ctx.claimed_tile() {
+                    Some(t) => !m.tiles().contains(t) || eye.tiles().contains(t),
+                    None => true,
+                })
+                .count()
+                + hand
+                    .melds()
+                    .iter()
+                    .filter(|m| m.meld_type().eq(&MeldType::AnGang))
+                    .count();
+            let mut all_melds = hand.melds().clone();
+            all_melds.extend(poss_meld);
+            let mut patterns = hand_patterns.clone();
+            patterns.extend(self.meld_patterns(ctx, &all_melds, &eye, concealed_pongs));
+            hus.push(self.new_hu(all_melds, patterns));
+        }
+
+        hus.sort_by(|a, b| b.cmp(a));
+        hus.dedup();
+        hus
+    }
+
+    fn meld_patterns(
+        &self,
+        ctx: &WinContext,
+        all_melds: &[Meld],
+        eye: &Meld,
+        concealed_pongs: usize,
+    ) -> Vec<Tai> {
+        let mut patterns = Vec::new();
+        let sets: Vec<&Meld> = all_melds
+            .iter()
+            .filter(|m| !m.meld_type().eq(&MeldType::Eye))
+            .collect();
+        let pongs: Vec<&Meld> = sets
+            .iter()
+            .filter(|m| !m.meld_type().eq(&MeldType::Chi))
+            .copied()
+            .collect();
+
+        if pongs.is_empty() && ctx.claimed_tile().is_some() && !eye.suit().eq(&Suit::Dragon) {
+            patterns.push(Tai::AllChows);
+        }
+        if pongs.len() == sets.len() {
+            patterns.push(Tai::AllPongs);
+        }
+        match concealed_pongs {
+            5 => patterns.push(Tai::FiveConcealedPongs),
+            4 => patterns.push(Tai::FourConcealedPongs),
+            3 => patterns.push(Tai::ThreeConcealedPongs),
+            _ => (),
+        }
+
+        let dragon_pongs = pongs.iter().filter(|m| m.suit().eq(&Suit::Dragon)).count();
+        match (dragon_pongs, eye.suit()) {
+            (3, _) => patterns.push(Tai::GreatThreeDragons),
+            (2, Suit::Dragon) => patterns.push(Tai::SmallThreeDragons),
+            (n, _) => patterns.extend(vec![Tai::DragonPong; n]),
+        }
+
+        let wind_pongs: Vec<&Tile> = pongs
+            .iter()
+            .filter_map(|m| m.tiles().first())
+            .filter(|t| matches!(t, Tile::Wind(_)))
+            .collect();
+        match (wind_pongs.len(), eye.suit()) {
+            (4, _) => patterns.push(Tai::GreatFourWinds),
+            (3, Suit::Wind) => patterns.push(Tai::SmallFourWinds),
+            _ => {
+                for tile in wind_pongs {
+                    if tile.eq(&Tile::Wind(ctx.seat_wind)) {
+                        patterns.push(Tai::SeatWind);
+                    }
+                    if tile.eq(&Tile::Wind(ctx.prevailing_wind)) {
+                        patterns.push(Tai::PrevailingWind);
+                    }
+                }
+            }
+        }
+
+        let mut number_suits: Vec<Suit> = all_melds
+            .iter()
+            .map(|m| *m.suit())
+            .filter(|s| matches!(s, Suit::Wan | Suit::Suo | Suit::Tong))
+            .collect();
+        number_suits.sort();
+        number_suits.dedup();
+        let has_honours = all_melds.iter().any(|m| m.tiles()[0].is_honour());
+        match (number_suits.len(), has_honours) {
+            (0, _) => patterns.push(Tai::AllHonours),
+            (1, true) => patterns.push(Tai::MixedOneSuit),
+            (1, false) => patterns.push(Tai::PureOneSuit),
+            _ => (),
+        }
+        patterns
+    }
+
+    pub fn search_hu(&self, hand: &Hand, ctx: &WinContext) -> Option<TaiwanHu> {
+        self.search_hus(hand, ctx).into_iter().next()
+    }
+
+    // A discarder pays alone, a self-drawn win is paid by everyone. Whenever the dealer wins
+    // or pays, the payment gains one tai plus streak_tai for every consecutive dealer win.
+    pub fn payments(&self, hu: &TaiwanHu, ctx: &WinContext) -> Vec<(Wind, u32)> {
+        ctx.seats
+            .iter()
+            .copied()
+            .filter(|w| w != &ctx.seat_wind)
+            .filter(|w| ctx.self_drawn || ctx.discarder == Some(*w))
+            .map(|w| {
+                let mut tai = hu.tai as u32;
+                if ctx.is_dealer() || ctx.dealer == Some(w) {
+                    tai += self.streak_tai * ctx.dealer_streak as u32;
+                    if !ctx.is_dealer() {
+                        tai += *self.tai.get(&Tai::Dealer).unwrap_or(&0) as u32;
+                    }
+                }
+                (w, self.base + tai * self.per_tai)
+            })
+            .collect()
+    }
+}
+
+impl RuleSet for Taiwanese {
+    type Pattern = Tai;
+
+    fn score_all(&self, hand: &Hand, ctx: &WinContext) -> Vec<Scoring<Tai>> {
+        self.search_hus(hand, ctx)
+            .into_iter()
+            .map(|hu| Scoring {
+                melds: hu.melds,
+                patterns: hu.patterns,
+                value: hu.tai as u32,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        context::WinContext,
+        hand::Hand,
+        hu::search_hu,
+        tai::ScoreTai,
+        tile::{Dragon, Tile, TileValue, Wind},
+    };
+
+    use super::{Tai, Taiwanese};
+
+    // 123m 456m 789m 234p 55s Fa Fa, waiting on 5s or Fa
+    fn sixteen_tile_hand() -> Hand {
+        use TileValue::*;
+        let mut hand = Hand::new();
+        for tile in [
+            Tile::Wan(One),
+            Tile::Wan(Two),
+            Tile::Wan(Three),
+            Tile::Wan(Four),
+            Tile::Wan(Five),
+            Tile::Wan(Six),
+            Tile::Wan(Seven),
+            Tile::Wan(Eight),
+            Tile::Wan(Nine),
+            Tile::Tong(Two),
+            Tile::Tong(Three),
+            Tile::Tong(Four),
+            Tile::Suo(Five),
+            Tile::Suo(Five),
+            Tile::Dragon(Dragon::Fa),
+            Tile::Dragon(Dragon::Fa),
+        ] {
+            hand.draw(&tile);
+        }
+        hand
+    }
+
+    #[test]
+    fn test_five_sets_and_an_eye() {
+        let hand = sixteen_tile_hand();
+        assert_eq!(
+            hand.concealed().values().sum::<u8>() as usize,
+            Taiwanese::SHAPE.hand_size()
+        );
+        let ctx = WinContext::discard(
+            Tile::Dragon(Dragon::Fa),
+            Wind::West,
+            Wind::South,
+            Wind::East,
+        );
+        let hu = Taiwanese::default().search_hu(&hand, &ctx).unwrap();
+        assert_eq!(hu.melds().len(), 6);
+        assert_eq!(hu.patterns(), &vec![Tai::Concealed, Tai::DragonPong]);
+        assert_eq!(hu.tai(), 2);
+
+        // Four sets plus an eye is not a complete Singapore hand with 17 tiles
+        assert_eq!(search_hu(&hand, &ctx, &ScoreTai::singapore()), None);
+    }
+
+    #[test]
+    fn test_dealer_streak_payments() {
+        let mut hand = sixteen_tile_hand();
+        hand.draw(&Tile::Suo(TileValue::Five));
+        let rules = Taiwanese::default();
+
+        let ctx = WinContext::self_drawn(Tile::Suo(TileValue::Five), Wind::East, Wind::East)
+            .with_dealer()
+            .with_dealer_streak(1);
+        let hu = rules.search_hu(&hand, &ctx).unwrap();
+        assert_eq!(hu.patterns(), &vec![Tai::Dealer, Tai::ConcealedSelfDrawn]);
+        assert_eq!(hu.tai(), 4);
+        // 4 tai plus 2 for one consecutive dealer win
+        assert!(rules
+            .payments(&hu, &ctx)
+            .iter()
+            .all(|(_, p)| *p == 100 + 6 * 20));
+
+        let ctx = WinContext::self_drawn(Tile::Suo(TileValue::Five), Wind::South, Wind::East)
+            .with_dealer_seat(Wind::West)
+            .with_dealer_streak(2);
+        let hu = rules.search_hu(&hand, &ctx).unwrap();
+        assert_eq!(hu.tai(), 3);
+        // The dealer pays 3 tai, plus 1 as dealer and 4 for two consecutive wins
+        assert_eq!(
+            rules.payments(&hu, &ctx),
+            vec![
+                (Wind::East, 160),
+                (Wind::West, 100 + 8 * 20),
+                (Wind::North, 160)
+            ]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{
+    error::MahjongError,
+    round::{Rotation, Round, RoundRules},
+    tile::{Suit, Tile, TileValue, Wind},
+};
+
+// Three-player mahjong: some tiles are taken out of the set, North is left empty and chi is
+// not allowed. Payments are settled between the three seats only.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreePlayer {
+    removed: Vec<Tile>,
+}
+
+impl ThreePlayer {
+    pub const SEATS: [Wind; 3] = [Wind::East, Wind::South, Wind::West];
+
+    pub fn without_suit(suit: Suit) -> Self {
+        Self {
+            removed: Tile::playable_tiles()
+                .into_iter()
+                .filter(|t| t.suit() == suit)
+                .collect(),
+        }
+    }
+
+    pub fn without_tiles(tiles: &[Tile]) -> Self {
+        let mut removed: Vec<Tile> = tiles.iter().map(|t| t.normalized()).collect();
+        removed.sort();
+        removed.dedup();
+        Self { removed }
+    }
+
+    // Sanma as played in Japan, where only the 1 and 9 of wan remain
+    pub fn riichi() -> Self {
+        Self {
+            removed: (2..=8)
+                .filter_map(TileValue::from_number)
+                .map(Tile::Wan)
+                .collect(),
+        }
+    }
+
+    pub fn removed(&self) -> &Vec<Tile> {
+        &self.removed
+    }
+
+    pub fn is_removed(&self, tile: &Tile) -> bool {
+        self.removed.contains(&tile.normalized())
+    }
+
+    // Four of every tile still in play, unshuffled
+    pub fn wall(&self) -> Vec<Tile> {
+        Tile::playable_tiles()
+            .into_iter()
+            .filter(|t| !self.is_removed(t))
+            .flat_map(|t| [t; 4])
+            .collect()
+    }
+
+    pub fn rules(&self) -> RoundRules {
+        RoundRules {
+            chi: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        Rotation::new(Self::SEATS.len()).unwrap()
+    }
+
+    pub fn new_round(&self, rotation: &Rotation, wall: Vec<Tile>) -> Result<Round, MahjongError> {
+        if let Some(tile) = wall.iter().find(|t| self.is_removed(t)) {
+            return Err(MahjongError::RemovedTileError(*tile));
+        }
+        Ok(rotation.new_round(wall, self.rules()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::{assert_err, assert_matches};
+
+    use crate::{
+        error::MahjongError,
+        meld::{Meld, MeldType},
+        tile::{Suit, Tile, TileValue, Wind},
+    };
+
+    use super::ThreePlayer;
+
+    #[test]
+    fn test_removed_tiles() {
+        let sanma = ThreePlayer::riichi();
+        assert_eq!(sanma.removed().len(), 7);
+        assert_eq!(sanma.wall().len(), 108);
+        assert!(sanma.is_removed(&Tile::Wan(TileValue::Five)));
+        assert!(!sanma.is_removed(&Tile::Wan(TileValue::Nine)));
+
+        let no_tong = ThreePlayer::without_suit(Suit::Tong);
+        assert_eq!(no_tong.wall().len(), 100);
+        assert_err!(no_tong.new_round(&no_tong.rotation(), sanma.wall()));
+    }
+
+    #[test]
+    fn test_no_chi() {
+        let sanma = ThreePlayer::riichi();
+        let mut round = sanma.new_round(&sanma.rotation(), sanma.wall()).unwrap();
+        assert_eq!(round.active_seats(), ThreePlayer::SEATS.to_vec());
+
+        let tile = round.draw().unwrap();
+        assert_eq!(tile, Tile::Suo(TileValue::Eight));
+        round.discard(Wind::East, &tile).unwrap();
+        let chi = Meld::new(
+            vec![Tile::Suo(TileValue::Six), Tile::Suo(TileValue::Seven)],
+            Some(tile),
+            MeldType::Chi,
+        )
+        .unwrap();
+        assert_matches!(
+            round.claim(Wind::South, chi),
+            Err(MahjongError::ClaimNotAllowedError(MeldType::Chi))
+        );
+
+        let ctx = round.win_context(Wind::South).unwrap();
+        assert_eq!(ctx.seats, ThreePlayer::SEATS.to_vec());
+        assert_eq!(ctx.dealer, Some(Wind::East));
+        assert!(!ctx.is_dealer());
+    }
+}
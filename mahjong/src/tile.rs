@@ -7,6 +7,8 @@ pub enum Tile {
     Dragon(Dragon),
     Animal(Animal),
     Flower(Flower),
+    // American wildcard, only usable in place of a tile in a pong or larger group
+    Joker,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
@@ -16,6 +18,8 @@ pub enum TileValue {
     Three,
     Four,
     Five,
+    // Aka-dora, otherwise played as a regular five
+    RedFive,
     Six,
     Seven,
     Eight,
@@ -51,6 +55,12 @@ pub enum Flower {
     Blue(FlowerValue),
 }
 
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum FlowerColour {
+    Red,
+    Blue,
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum FlowerValue {
     One,
@@ -68,6 +78,7 @@ pub enum Suit {
     Dragon,
     Animal,
     Flower,
+    Joker,
 }
 
 impl Tile {
@@ -98,12 +109,105 @@ impl Tile {
             Tile::Dragon(_) => Suit::Dragon,
             Tile::Animal(_) => Suit::Animal,
             Tile::Flower(_) => Suit::Flower,
+            Tile::Joker => Suit::Joker,
         }
     }
 
     pub fn is_playable(&self) -> bool {
         !matches!(self, Tile::Animal(_) | Tile::Flower(_))
     }
+
+    // Red fives are treated as regular fives
+    pub fn normalized(&self) -> Tile {
+        match self {
+            Tile::Wan(val) => Tile::Wan(val.normalized()),
+            Tile::Suo(val) => Tile::Suo(val.normalized()),
+            Tile::Tong(val) => Tile::Tong(val.normalized()),
+            _ => *self,
+        }
+    }
+
+    pub fn is_red(&self) -> bool {
+        matches!(
+            self,
+            Tile::Wan(TileValue::RedFive)
+                | Tile::Suo(TileValue::RedFive)
+                | Tile::Tong(TileValue::RedFive)
+        )
+    }
+
+    // The 34 kinds of playable tiles, without red fives
+    pub fn playable_tiles() -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        let mut value = Some(TileValue::One);
+        while let Some(v) = value {
+            tiles.extend([Tile::Wan(v), Tile::Suo(v), Tile::Tong(v)]);
+            value = v.next();
+        }
+        tiles.extend(Wind::ALL.map(Tile::Wind));
+        tiles.extend([Dragon::Zhong, Dragon::Fa, Dragon::Baiban].map(Tile::Dragon));
+        tiles.sort();
+        tiles
+    }
+
+    // Face value of a numbered tile
+    pub fn number(&self) -> Option<u8> {
+        match self {
+            Tile::Wan(val) | Tile::Suo(val) | Tile::Tong(val) => Some(val.number()),
+            _ => None,
+        }
+    }
+
+    pub fn is_honour(&self) -> bool {
+        matches!(self, Tile::Wind(_) | Tile::Dragon(_))
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            Tile::Wan(TileValue::One | TileValue::Nine)
+                | Tile::Suo(TileValue::One | TileValue::Nine)
+                | Tile::Tong(TileValue::One | TileValue::Nine)
+        )
+    }
+
+    // [1Wan, 9Wan, 1Suo, 9Suo, 1Tong, 9Tong, Zhong, Fa, Baiban, East, South, West, North]
+    pub fn terminals_and_honours() -> [Tile; 13] {
+        [
+            Tile::Wan(TileValue::One),
+            Tile::Wan(TileValue::Nine),
+            Tile::Suo(TileValue::One),
+            Tile::Suo(TileValue::Nine),
+            Tile::Tong(TileValue::One),
+            Tile::Tong(TileValue::Nine),
+            Tile::Dragon(Dragon::Zhong),
+            Tile::Dragon(Dragon::Fa),
+            Tile::Dragon(Dragon::Baiban),
+            Tile::Wind(Wind::East),
+            Tile::Wind(Wind::South),
+            Tile::Wind(Wind::West),
+            Tile::Wind(Wind::North),
+        ]
+    }
+}
+
+impl Wind {
+    pub const ALL: [Wind; 4] = [Wind::East, Wind::South, Wind::West, Wind::North];
+}
+
+impl Flower {
+    pub fn colour(&self) -> FlowerColour {
+        match self {
+            Flower::Red(_) => FlowerColour::Red,
+            Flower::Blue(_) => FlowerColour::Blue,
+        }
+    }
+
+    pub fn value(&self) -> FlowerValue {
+        match self {
+            Flower::Red(value) | Flower::Blue(value) => *value,
+        }
+    }
 }
 
 impl TileValue {
@@ -113,7 +217,7 @@ impl TileValue {
             TileValue::Two => Some(TileValue::Three),
             TileValue::Three => Some(TileValue::Four),
             TileValue::Four => Some(TileValue::Five),
-            TileValue::Five => Some(TileValue::Six),
+            TileValue::Five | TileValue::RedFive => Some(TileValue::Six),
             TileValue::Six => Some(TileValue::Seven),
             TileValue::Seven => Some(TileValue::Eight),
             TileValue::Eight => Some(TileValue::Nine),
@@ -127,13 +231,49 @@ impl TileValue {
             TileValue::Two => Some(TileValue::One),
             TileValue::Three => Some(TileValue::Two),
             TileValue::Four => Some(TileValue::Three),
-            TileValue::Five => Some(TileValue::Four),
+            TileValue::Five | TileValue::RedFive => Some(TileValue::Four),
             TileValue::Six => Some(TileValue::Five),
             TileValue::Seven => Some(TileValue::Six),
             TileValue::Eight => Some(TileValue::Seven),
             TileValue::Nine => Some(TileValue::Eight),
         }
     }
+
+    pub fn normalized(&self) -> TileValue {
+        match self {
+            TileValue::RedFive => TileValue::Five,
+            _ => *self,
+        }
+    }
+
+    pub fn from_number(number: u8) -> Option<TileValue> {
+        match number {
+            1 => Some(TileValue::One),
+            2 => Some(TileValue::Two),
+            3 => Some(TileValue::Three),
+            4 => Some(TileValue::Four),
+            5 => Some(TileValue::Five),
+            6 => Some(TileValue::Six),
+            7 => Some(TileValue::Seven),
+            8 => Some(TileValue::Eight),
+            9 => Some(TileValue::Nine),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self) -> u8 {
+        match self {
+            TileValue::One => 1,
+            TileValue::Two => 2,
+            TileValue::Three => 3,
+            TileValue::Four => 4,
+            TileValue::Five | TileValue::RedFive => 5,
+            TileValue::Six => 6,
+            TileValue::Seven => 7,
+            TileValue::Eight => 8,
+            TileValue::Nine => 9,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -172,6 +312,25 @@ mod tests {
         assert_eq!(tile.prev(), None);
     }
 
+    #[test]
+    fn test_red_five() {
+        let tile = Tile::Tong(TileValue::RedFive);
+        assert!(tile.is_red());
+        assert_eq!(tile.normalized(), Tile::Tong(TileValue::Five));
+        assert_eq!(tile.next(), Some(Tile::Tong(TileValue::Six)));
+        assert_eq!(tile.prev(), Some(Tile::Tong(TileValue::Four)));
+        assert_eq!(Tile::playable_tiles().len(), 34);
+    }
+
+    #[test]
+    fn test_tile_terminal_and_honour() {
+        assert!(Tile::Tong(TileValue::Nine).is_terminal());
+        assert!(!Tile::Tong(TileValue::Eight).is_terminal());
+        assert!(!Tile::Wind(Wind::East).is_terminal());
+        assert!(Tile::Wind(Wind::East).is_honour());
+        assert!(!Tile::Wan(TileValue::One).is_honour());
+    }
+
     #[test]
     fn test_tile_prev_not_a_value_tile() {
         let tile = Tile::Wind(Wind::East);
@@ -0,0 +1,115 @@
+use crate::{
+    error::MahjongError,
+    hand::{ConcealedTiles, River},
+    meld::Meld,
+    round::Round,
+    tile::{Tile, Wind},
+};
+
+// What everyone at the table can see of a seat
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatView {
+    pub seat: Wind,
+    pub concealed_count: usize,
+    pub melds: Vec<Meld>,
+    pub bonus: Vec<Tile>,
+    pub river: River,
+    pub won: bool,
+}
+
+// The table as one player sees it, holding no concealed tile but their own
+#[derive(Debug, Clone)]
+pub struct PlayerView {
+    pub seat: Wind,
+    pub concealed: ConcealedTiles,
+    // Every seat in turn order, the player's own included
+    pub seats: Vec<SeatView>,
+    pub current: Wind,
+    pub prevailing_wind: Wind,
+    pub wall_remaining: usize,
+    pub last_discard: Option<(Wind, Tile)>,
+}
+
+impl PlayerView {
+    pub fn seat_view(&self, seat: Wind) -> Option<&SeatView> {
+        self.seats.iter().find(|s| s.seat == seat)
+    }
+
+    pub fn opponents(&self) -> impl Iterator<Item = &SeatView> {
+        self.seats.iter().filter(move |s| s.seat != self.seat)
+    }
+}
+
+impl Round {
+    pub fn view(&self, seat: Wind) -> Result<PlayerView, MahjongError> {
+        let player = self
+            .player(seat)
+            .ok_or(MahjongError::SeatNotAtTableError(seat))?;
+        let seats = self
+            .players()
+            .iter()
+            .map(|p| {
+                let hand = p.hand();
+                let mut bonus: Vec<Tile> = hand.bonus().iter().copied().collect();
+                bonus.sort();
+                SeatView {
+                    seat: p.seat(),
+                    concealed_count: hand.concealed().values().map(|c| *c as usize).sum(),
+                    melds: hand.melds().clone(),
+                    bonus,
+                    river: hand.river().clone(),
+                    won: p.has_won(),
+                }
+            })
+            .collect();
+        Ok(PlayerView {
+            seat,
+            concealed: player.hand().concealed().clone(),
+            seats,
+            current: self.current(),
+            prevailing_wind: self.prevailing_wind(),
+            wall_remaining: self.wall().len(),
+            last_discard: self.last_discard(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::assert_matches;
+
+    use crate::{
+        error::MahjongError,
+        round::{Round, RoundRules},
+        tile::{Tile, TileValue, Wind},
+    };
+
+    #[test]
+    fn test_player_view() {
+        let mut wall = Vec::new();
+        for number in 1..=9 {
+            let value = TileValue::from_number(number).unwrap();
+            for _ in 0..4 {
+                wall.extend([Tile::Wan(value), Tile::Suo(value), Tile::Tong(value)]);
+            }
+        }
+        let mut round = Round::new(&Wind::ALL, wall, Wind::East, RoundRules::default());
+        let tile = round.draw().unwrap();
+        round.discard(Wind::East, &tile).unwrap();
+
+        let view = round.view(Wind::South).unwrap();
+        let hand = round.player(Wind::South).unwrap().hand();
+        assert_eq!(*view.concealed, **hand.concealed());
+        assert_eq!(view.opponents().count(), 3);
+        let east = view.seat_view(Wind::East).unwrap();
+        assert_eq!(east.concealed_count, 13);
+        assert_eq!(east.river[0].tile, tile);
+        assert_eq!(view.current, Wind::South);
+        assert_eq!(view.wall_remaining, 55);
+        assert_matches!(
+            Round::new(&Wind::ALL[..3], vec![], Wind::East, RoundRules::default())
+                .view(Wind::North),
+            Err(MahjongError::SeatNotAtTableError(Wind::North))
+        );
+    }
+}